@@ -0,0 +1,71 @@
+// `create_mock!` can mock methods that return boxed futures or `impl Future`,
+// as well as `async` methods. This example is on the 2015 edition, which has
+// no `async fn`, but an `async fn` is mocked just like a method returning
+// `impl Future`.
+//
+// Expectations for these methods are set up with the output type of the future,
+// so `.returning()` works the same as for any other method. The returned future
+// resolves to that value, or stays pending until the test resolves it when
+// `.returning_pending()` is used instead.
+//
+// `block_on()` and `poll_once()` are enough to drive these futures in a test,
+// without needing an async runtime.
+
+extern crate simulacrum;
+
+use simulacrum::*;
+
+use std::future::Future;
+use std::pin::Pin;
+
+trait Service {
+    fn fetch(&self, id: u32) -> Pin<Box<dyn Future<Output = String>>>;
+
+    fn reset(&mut self) -> impl Future<Output = ()>;
+
+    fn status(&self) -> Pin<Box<dyn Future<Output = bool> + Send>>;
+
+    fn count(&self, filter: &str) -> impl Future<Output = usize>;
+}
+
+create_mock! {
+    impl Service for ServiceMock (self) {
+        expect_fetch("fetch"):
+        fn fetch(&self, id: u32) -> Pin<Box<dyn Future<Output = String>>>;
+
+        expect_reset("reset"):
+        fn reset(&mut self) -> impl Future<Output = ()>;
+
+        expect_status("status"):
+        fn status(&self) -> Pin<Box<dyn Future<Output = bool> + Send>>;
+
+        expect_count("count"):
+        fn count(&self, filter: &str) -> impl Future<Output = usize>;
+    }
+}
+
+fn main() {
+    // Create a mock object
+    let mut m = ServiceMock::new();
+
+    // Set up expectations for it
+    m.expect_fetch().called_once().with(7).returning(|id| format!("item {}", id));
+    m.expect_reset().called_once();
+    m.expect_status().called_once().returning(|_| true);
+    m.expect_count().called_once().with(deref("open")).returning(|filter| filter.with(|filter| filter.len()));
+
+    // Execute test code
+    assert_eq!(block_on(m.fetch(7)), "item 7");
+    block_on(m.reset());
+    assert!(block_on(m.status()));
+    assert_eq!(block_on(m.count("open")), 4);
+
+    // A pending future only completes once the test resolves it
+    let resolver = m.then().expect_fetch().called_once().returning_pending();
+    let mut fetching = Box::pin(m.fetch(8));
+    assert!(poll_once(&mut fetching).is_pending());
+    resolver.resolve("late item".to_owned());
+    assert_eq!(poll_once(&mut fetching), std::task::Poll::Ready("late item".to_owned()));
+
+    // When the mock object is dropped, its expectations will be evaluated
+}
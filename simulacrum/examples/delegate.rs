@@ -0,0 +1,83 @@
+// Mock objects created with `create_mock!` can forward calls to another
+// implementation of the same trait, while still checking their expectations.
+//
+// This is useful for wrapping a real or fake implementation in order to verify
+// how it is used.
+
+extern crate simulacrum;
+
+use simulacrum::*;
+
+trait Store {
+    fn get(&self, key: &'static str) -> Option<i32>;
+
+    fn set(&mut self, key: &'static str, value: i32);
+
+    fn total(&self, extra: &i32) -> i32;
+
+    fn clear(&mut self);
+}
+
+#[derive(Default)]
+struct FakeStore {
+    values: Vec<(&'static str, i32)>
+}
+
+impl Store for FakeStore {
+    fn get(&self, key: &'static str) -> Option<i32> {
+        self.values.iter().find(|&&(k, _)| k == key).map(|&(_, v)| v)
+    }
+
+    fn set(&mut self, key: &'static str, value: i32) {
+        self.values.retain(|&(k, _)| k != key);
+        self.values.push((key, value));
+    }
+
+    fn total(&self, extra: &i32) -> i32 {
+        self.values.iter().map(|&(_, v)| v).sum::<i32>() + *extra
+    }
+
+    fn clear(&mut self) {
+        self.values.clear();
+    }
+}
+
+create_mock! {
+    impl Store for StoreMock (self) {
+        expect_get("get"):
+        fn get(&self, key: &'static str) -> Option<i32>;
+
+        expect_set("set"):
+        fn set(&mut self, key: &'static str, value: i32);
+
+        expect_total("total"):
+        fn total(&self, extra: &i32) -> i32;
+
+        expect_clear("clear"):
+        fn clear(&mut self);
+    }
+}
+
+fn main() {
+    // Create a mock object that forwards calls to a fake implementation
+    let mut m = StoreMock::new();
+    m.delegate_to(FakeStore::default());
+
+    // Set up expectations for it. Methods without `returning()` use the fake.
+    m.expect_set().called_times(2);
+    m.expect_get().called_once().with("a");
+    m.then().expect_total().called_once().with(deref(8));
+    m.expect_clear().called_never();
+
+    // `returning()` still takes precedence over the fake
+    m.then().expect_get().called_once().returning(|_| Some(100));
+
+    // Execute test code
+    m.set("a", 1);
+    m.set("b", 2);
+    assert_eq!(m.get("a"), Some(1));
+    assert_eq!(m.total(&8), 11);
+    assert_eq!(m.get("b"), Some(100));
+
+    // When the mock object is dropped, its expectations will be evaluated
+}
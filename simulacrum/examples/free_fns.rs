@@ -0,0 +1,78 @@
+// `create_mock_fns!` can mock free functions, like the ones in a module of
+// helpers that other code calls directly.
+//
+// Outside of tests, the functions run their bodies as usual. In tests, they
+// check the expectations of the mock struct named at the top instead. Create
+// it with `with_statics()` to set up those expectations for the current
+// thread. They are verified when it is dropped.
+//
+// Run `cargo test --example free_fns` to see the functions mocked.
+
+extern crate simulacrum;
+
+mod fs_util {
+    use simulacrum::*;
+
+    create_mock_fns! {
+        struct FsUtilMock;
+
+        expect_read_config("read_config"):
+        /// Read a config file, returning `None` if it doesn't exist.
+        pub fn read_config(path: &str) -> Option<String> {
+            ::std::fs::read_to_string(path).ok()
+        }
+
+        expect_remove("remove"):
+        pub fn remove(path: &str) {
+            let _ = ::std::fs::remove_file(path);
+        }
+    }
+}
+
+// Code under test, which calls the functions directly
+fn port(path: &str) -> u16 {
+    match fs_util::read_config(path) {
+        Some(config) => config.trim().parse().unwrap_or(80),
+        None => 80
+    }
+}
+
+fn reset(path: &str) {
+    fs_util::remove(path);
+}
+
+fn main() {
+    // Not a test, so this reads the file for real
+    assert_eq!(port("/nonexistent/app.conf"), 80);
+    reset("/nonexistent/app.conf");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::fs_util::FsUtilMock;
+    use simulacrum::*;
+
+    #[test]
+    fn test_port() {
+        // Set up expectations for the functions
+        let mut m = FsUtilMock::with_statics();
+        m.expect_read_config().called_once().with(deref("app.conf")).returning(|_| Some("8080\n".to_owned()));
+        m.expect_remove().called_never();
+
+        // Execute test code
+        assert_eq!(port("app.conf"), 8080);
+
+        // When the mock object is dropped, its expectations will be evaluated
+    }
+
+    #[test]
+    #[should_panic(expected = "remove: Called 1 times more than expected.")]
+    fn test_reset_not_expected() {
+        let mut m = FsUtilMock::with_statics();
+        m.expect_remove().called_never();
+
+        // Panic: `remove` should never be called
+        reset("app.conf");
+    }
+}
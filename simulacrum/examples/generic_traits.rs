@@ -0,0 +1,85 @@
+// `create_mock!` can mock generic traits, traits with lifetime parameters and
+// traits with `where` clauses.
+//
+// Give the mock struct the same generic parameters as the impl, like you would
+// for any other generic impl. Type parameters must be `'static` so that
+// expectations can be stored for them.
+
+extern crate simulacrum;
+
+use simulacrum::*;
+
+use std::hash::Hash;
+
+trait Repo<K, V> where K: Hash + Eq {
+    fn get(&self, key: K) -> Option<V>;
+
+    fn insert(&mut self, key: K, value: V);
+}
+
+trait Parser<'a> {
+    fn parse(&self, input: &'a str) -> usize;
+}
+
+create_mock! {
+    impl<K, V> Repo<K, V> for RepoMock<K, V> (self) where K: Hash + Eq {
+        expect_get("get"):
+        fn get(&self, key: K) -> Option<V>;
+
+        expect_insert("insert"):
+        fn insert(&mut self, key: K, value: V);
+    }
+}
+
+create_mock! {
+    impl<'a> Parser<'a> for ParserMock<'a> (self) {
+        expect_parse("parse"):
+        fn parse(&self, input: &'a str) -> usize;
+    }
+}
+
+struct WordParser;
+
+impl<'a> Parser<'a> for WordParser {
+    fn parse(&self, input: &'a str) -> usize {
+        input.split_whitespace().count()
+    }
+}
+
+// A generic trait can also be mocked for specific types
+create_mock! {
+    impl Repo<u32, String> for NameRepoMock (self) {
+        expect_get("get"):
+        fn get(&self, key: u32) -> Option<String>;
+
+        expect_insert("insert"):
+        fn insert(&mut self, key: u32, value: String);
+    }
+}
+
+fn main() {
+    // Create mock objects
+    let mut repo = RepoMock::<&'static str, i32>::new();
+    let mut parser = ParserMock::new();
+    // Params borrowed for `'a` might outlive the call, so `parse()` isn't
+    // forwarded to the inner parser and still needs `returning()`
+    parser.delegate_to(WordParser);
+    let mut names = NameRepoMock::new();
+
+    // Set up expectations for them
+    repo.expect_insert().called_once().with(params!("a", 1));
+    repo.expect_get().called_once().with("a").returning(|_| Some(1));
+    parser.expect_parse().called_once().with(deref("12 + 3")).returning(|input| input.with(|input| input.len()));
+    names.expect_get().called_once().with(7).returning(|_| Some("seven".to_owned()));
+
+    // Execute test code
+    repo.insert("a", 1);
+    assert_eq!(repo.get("a"), Some(1));
+
+    let input = String::from("12 + 3");
+    assert_eq!(parser.parse(&input), 6);
+
+    assert_eq!(names.get(7), Some("seven".to_owned()));
+
+    // When the mock objects are dropped, their expectations will be evaluated
+}
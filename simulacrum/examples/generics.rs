@@ -0,0 +1,61 @@
+// `create_mock!` can mock generic methods, as long as their type parameters
+// are `'static`.
+//
+// Expectations are set up separately for each type that a generic method is
+// used with, by passing that type to the `.expect_METHOD_NAME()` method. Calls
+// with a type that has no expectations are reported as unexpected.
+
+extern crate simulacrum;
+
+use simulacrum::*;
+
+use std::fmt::Debug;
+
+trait Cache {
+    fn put<T: Debug + 'static>(&self, value: T);
+
+    fn describe<T: Into<String> + 'static>(&self, id: u32, value: T) -> String;
+
+    fn make<T: Default + 'static>(&self) -> T;
+}
+
+create_mock! {
+    impl Cache for CacheMock (self) {
+        expect_put("put"):
+        fn put<T: Debug + 'static>(&self, value: T);
+
+        expect_describe("describe"):
+        fn describe<T: Into<String> + 'static>(&self, id: u32, value: T) -> String;
+
+        expect_make("make"):
+        fn make<T: Default + 'static>(&self) -> T;
+    }
+}
+
+fn main() {
+    // Create a mock object
+    let mut m = CacheMock::new();
+
+    // Set up expectations for each type the methods are used with
+    m.expect_put::<i32>().called_once().with(5);
+    m.expect_put::<&'static str>().called_times(2);
+    m.expect_describe::<String>().called_once().returning(|(id, value)| format!("{}: {}", id, value));
+    m.expect_make::<u8>().called_once().returning(|_| 7);
+    m.expect_make::<Vec<bool>>().called_once().returning(|_| vec![true]);
+
+    // Execute test code
+    m.put(5);
+    m.put("hello");
+    m.put("world");
+    assert_eq!(m.describe(1, "one".to_owned()), "1: one");
+    assert_eq!(m.make::<u8>(), 7);
+    assert_eq!(m.make::<Vec<bool>>(), vec![true]);
+
+    // A call with a type that has no expectations is reported by name
+    m.put(1.5);
+    let report = m.verify_all().unwrap_err();
+    assert!(report.to_string().contains("Received: I = f64, O = ()"));
+
+    // Clear the expectations, so that dropping the mock object doesn't panic
+    m.checkpoint().unwrap_err();
+}
@@ -0,0 +1,118 @@
+// `create_mock!` can mock the inherent methods of a struct, for dependencies
+// that aren't behind a trait.
+//
+// Leave out the `for` and the trait name. The mock then gets the same methods,
+// plus the usual `expect_*` ones. Code under test can use a type alias that
+// points at the mock in tests, and at the real struct otherwise.
+//
+// Run `cargo test --example inherent` to see the struct mocked.
+
+extern crate simulacrum;
+
+use simulacrum::*;
+
+// The real struct goes unused in tests, where the mock replaces it
+#[cfg_attr(test, allow(dead_code))]
+struct Database {
+    url: String
+}
+
+#[cfg_attr(test, allow(dead_code))]
+impl Database {
+    fn connect(url: &str) -> Self {
+        Database { url: url.to_owned() }
+    }
+
+    fn query(&self, sql: &str) -> Vec<String> {
+        vec![format!("{}: {}", self.url, sql)]
+    }
+
+    fn execute(&mut self, sql: &str) -> usize {
+        sql.len()
+    }
+}
+
+create_mock! {
+    impl DatabaseMock (self) {
+        expect_connect("connect"):
+        fn connect(url: &str) -> Self;
+
+        expect_query("query"):
+        fn query(&self, sql: &str) -> Vec<String>;
+
+        expect_execute("execute"):
+        fn execute(&mut self, sql: &str) -> usize;
+    }
+}
+
+// Code under test, which uses the alias instead of the struct
+#[cfg(not(test))]
+type Db = Database;
+#[cfg(test)]
+type Db = DatabaseMock;
+
+fn count_users(db: &Db) -> usize {
+    db.query("SELECT name FROM users").len()
+}
+
+fn clear_users(db: &mut Db) -> usize {
+    db.execute("DELETE FROM users")
+}
+
+fn main() {
+    // Not a test, so this uses the real struct
+    let mut db = Db::connect("db://localhost");
+    assert_eq!(count_users(&db), 1);
+    assert_eq!(clear_users(&mut db), 17);
+
+    // The mock can also be used directly, without an alias
+    let mut m = DatabaseMock::new();
+    m.expect_query().called_once().with(deref("SELECT 1")).returning(|_| vec!["1".to_owned()]);
+    assert_eq!(m.query("SELECT 1"), vec!["1".to_owned()]);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_count_users() {
+        // Set up expectations for the mock
+        let mut m = DatabaseMock::new();
+        m.expect_query().called_once().returning(|_| vec!["alice".to_owned(), "bob".to_owned()]);
+        m.expect_execute().called_never();
+
+        // Execute test code
+        assert_eq!(count_users(&m), 2);
+
+        // When the mock object is dropped, its expectations will be evaluated
+    }
+
+    #[test]
+    fn test_connect() {
+        // Functions without `self` use the expectations of `with_statics()`
+        let mut statics = DatabaseMock::with_statics();
+        statics.expect_connect().called_once().with(deref("db://test")).returning(|_| {
+            let mut db = DatabaseMock::new();
+            db.expect_execute().called_once().returning(|_| 3);
+            db
+        });
+
+        let mut db = Db::connect("db://test");
+        assert_eq!(clear_users(&mut db), 3);
+    }
+
+    #[test]
+    fn test_call_log() {
+        let mut m = DatabaseMock::new();
+        m.record_calls();
+        m.expect_query().called_once().returning(|_| Vec::new());
+
+        count_users(&m);
+
+        // Params that implement `Debug` are printed in the log
+        let log = m.call_log();
+        assert_eq!(log[0].method_name, "query");
+        assert_eq!(log[0].args, "&\"SELECT name FROM users\"");
+    }
+}
@@ -33,6 +33,9 @@ trait CoolTrait {
     // Mutable reference
     fn toggle(&self, bit: &mut bool);
 
+    // String and slice references
+    fn greet(&self, name: &str, scores: &[u32]) -> String;
+
     // Unsafe
     unsafe fn ohno(&self);
 }
@@ -55,13 +58,16 @@ create_mock! {
         expect_boop("boop"):
         fn boop(&self, name: &'static str);
 
-        // & params are mocked as *const and &mut are mocked as *mut.
+        // & params are mocked as Borrowed and &mut are mocked as BorrowedMut.
         expect_store("store"):
         fn store(&self, val: &i64);
 
         expect_toggle("toggle"): 
         fn toggle(&self, bit: &mut bool);
 
+        expect_greet("greet"):
+        fn greet(&self, name: &str, scores: &[u32]) -> String;
+
         expect_ohno("ohno"):
         unsafe fn ohno(&self);
     }
@@ -79,7 +85,9 @@ fn main() {
     m.expect_boop().called_times(2);
     m.expect_store().called_once().with(deref(777));
     m.expect_toggle().called_once().with(deref(true))
-                                   .modifying(|&mut arg| { unsafe { *arg = false } });
+                                   .modifying(|arg| arg.with_mut(|arg| *arg = false));
+    m.expect_greet().called_once().with(params!(deref("Ada"), deref(vec![1, 2])))
+                    .returning(|(name, scores)| name.with(|name| format!("{} scored {}", name, scores.with(|scores| scores.len()))));
     m.expect_ohno().called_once();
 
     // Execute test code
@@ -92,6 +100,8 @@ fn main() {
     let mut b = true;
     m.toggle(&mut b);
     assert_eq!(b, false);
+    let name = String::from("Ada");
+    assert_eq!(m.greet(&name, &[1, 2]), "Ada scored 2");
     unsafe {
         m.ohno();
     }
@@ -6,9 +6,9 @@
 // Note that if you want more control over your Mock object, you should look into
 // the low-level API available in the `simulacrum` crate.
 //
-// You can see that & and &mut parameters are mocked as *const and *mut. Also note
-// that the *mut parameter uses `was_called!()` with a `()` return type and
-// `.returning()` to have its return behavior specified.
+// You can see that & and &mut parameters are mocked as `Borrowed` and
+// `BorrowedMut`, whose values can only be used through `with()` and
+// `with_mut()` during the call.
 
 extern crate simulacrum;
 
@@ -44,8 +44,8 @@ create_mock_struct! {
         expect_goop("goop") bool => u32;
         expect_zing("zing") (i32, bool);
         expect_boop("boop") &'static str;
-        expect_store("store") *const i64;
-        expect_toggle("toggle") *mut bool;
+        expect_store("store") Borrowed<i64>;
+        expect_toggle("toggle") BorrowedMut<bool>;
     }
 }
 
@@ -71,11 +71,11 @@ impl CoolTrait for CoolTraitMock {
     }
 
     fn store(&self, val: &i64) {
-        was_called!(self, "store", (val: *const i64))
+        was_called!(self, "store", (val: &i64))
     }
 
     fn toggle(&self, bit: &mut bool) {
-        was_called!(self, "toggle", (bit: *mut bool))
+        was_called!(self, "toggle", (bit: &mut bool))
     }
 }
 
@@ -91,7 +91,7 @@ fn main() {
     m.expect_boop().called_times(2);
     m.expect_store().called_once().with(deref(777));
     m.expect_toggle().called_once().with(deref(true))
-                                   .modifying(|&mut arg| { unsafe { *arg = false } });
+                                   .modifying(|arg| arg.with_mut(|arg| *arg = false));
 
     // Execute test code
     m.foo();
@@ -28,6 +28,19 @@ trait CoolTrait {
     fn toggle(&self, bit: &mut bool);
 }
 
+// Typed keys for each method. These let the compiler check that expectations
+// and calls agree on each method's types. Plain method names work too, e.g.
+// `self.e.expect::<bool, u32>("goop")`.
+const FOO: MethodKey<(), ()> = MethodKey::new("foo");
+const BAR: MethodKey<(), ()> = MethodKey::new("bar");
+const GOOP: MethodKey<bool, u32> = MethodKey::new("goop");
+const ZING: MethodKey<(i32, bool), ()> = MethodKey::new("zing");
+const BOOP: MethodKey<&'static str, ()> = MethodKey::new("boop");
+// References are passed as `Borrowed` and `BorrowedMut`, which are created from
+// a `Scope` that lasts for the duration of the call.
+const STORE: MethodKey<Borrowed<i64>, ()> = MethodKey::new("store");
+const TOGGLE: MethodKey<BorrowedMut<bool>, ()> = MethodKey::new("toggle");
+
 pub struct CoolTraitMock {
     e: Expectations
 }
@@ -45,61 +58,61 @@ impl CoolTraitMock {
     }
 
     pub fn expect_foo(&mut self) -> Method<(), ()> {
-        self.e.expect::<(), ()>("foo")
+        self.e.expect(FOO)
     }
 
     pub fn expect_bar(&mut self) -> Method<(), ()> {
-        self.e.expect::<(), ()>("bar")
+        self.e.expect(BAR)
     }
 
     pub fn expect_goop(&mut self) -> Method<bool, u32> {
-        self.e.expect::<bool, u32>("goop")
+        self.e.expect(GOOP)
     }
 
     pub fn expect_zing(&mut self) -> Method<(i32, bool), ()> {
-        self.e.expect::<(i32, bool), ()>("zing")
+        self.e.expect(ZING)
     }
 
     pub fn expect_boop(&mut self) -> Method<&'static str, ()> {
-        self.e.expect::<&'static str, ()>("boop")
+        self.e.expect(BOOP)
     }
 
-    pub fn expect_store(&mut self) -> Method<*const i64, ()> {
-        self.e.expect::<*const i64, ()>("store")
+    pub fn expect_store(&mut self) -> Method<Borrowed<i64>, ()> {
+        self.e.expect(STORE)
     }
 
-    pub fn expect_toggle(&mut self) -> Method<*mut bool, ()> {
-        self.e.expect::<*mut bool, ()>("toggle")
+    pub fn expect_toggle(&mut self) -> Method<BorrowedMut<bool>, ()> {
+        self.e.expect(TOGGLE)
     }
 }
 
 impl CoolTrait for CoolTraitMock {
     fn foo(&self) {
-        self.e.was_called::<(), ()>("foo", ())
+        self.e.was_called(FOO, ())
     }
 
     fn bar(&mut self) {
-        self.e.was_called::<(), ()>("bar", ())
+        self.e.was_called(BAR, ())
     }
 
     fn goop(&mut self, flag: bool) -> u32 {
-        self.e.was_called_returning::<bool, u32>("goop", flag)
+        self.e.was_called_returning(GOOP, flag)
     }
 
     fn zing(&self, first: i32, second: bool) {
-        self.e.was_called::<(i32, bool), ()>("zing", (first, second))
+        self.e.was_called(ZING, (first, second))
     }
 
     fn boop(&self, name: &'static str) {
-        self.e.was_called::<&'static str, ()>("boop", name)
+        self.e.was_called(BOOP, name)
     }
 
     fn store(&self, val: &i64) {
-        self.e.was_called::<*const i64, ()>("store", val)
+        Scope::with(|scope| self.e.was_called(STORE, scope.borrow(val)))
     }
 
     fn toggle(&self, bit: &mut bool) {
-        self.e.was_called::<*mut bool, ()>("toggle", bit)
+        Scope::with(|scope| self.e.was_called(TOGGLE, scope.borrow_mut(bit)))
     }
 }
 
@@ -115,7 +128,7 @@ fn main() {
     m.expect_boop().called_times(2);
     m.expect_store().called_once().with(deref(777));
     m.expect_toggle().called_once().with(deref(true))
-                                   .modifying(|&mut arg| { unsafe { *arg = false } });
+                                   .modifying(|arg| arg.with_mut(|arg| *arg = false));
 
     // Execute test code
     m.foo();
@@ -0,0 +1,83 @@
+// `create_mock!` can mock several traits with one mock object, for code that
+// needs a value implementing all of them.
+//
+// Give the mock a `struct` section with an `impl` section for each trait. All
+// the methods share one set of expectations, so eras set up with `then()`
+// span every trait. Each method's name is prefixed with its trait's name,
+// like "Reader::read", so that methods of different traits can't collide.
+
+extern crate simulacrum;
+
+use simulacrum::*;
+
+trait Reader {
+    fn read(&mut self, buf: &mut [u8]) -> usize;
+}
+
+trait Writer {
+    fn write(&mut self, data: &[u8]) -> usize;
+
+    fn flush(&mut self);
+}
+
+trait Seek {
+    fn seek(&mut self, pos: u64) -> u64;
+}
+
+// Trait objects can only name one trait besides auto traits, so code that
+// needs all of them uses a trait that combines them.
+trait Stream: Reader + Writer + Seek {}
+
+impl<T: Reader + Writer + Seek> Stream for T {}
+
+create_mock! {
+    struct StreamMock (self) {
+        impl Reader {
+            expect_read("read"):
+            fn read(&mut self, buf: &mut [u8]) -> usize;
+        }
+
+        impl Writer {
+            expect_write("write"):
+            fn write(&mut self, data: &[u8]) -> usize;
+
+            expect_flush("flush"):
+            fn flush(&mut self);
+        }
+
+        impl Seek {
+            expect_seek("seek"):
+            fn seek(&mut self, pos: u64) -> u64;
+        }
+    }
+}
+
+// Code under test, which rewrites the start of a stream
+fn patch_header(stream: &mut Box<dyn Stream>) -> usize {
+    let mut header = [0; 4];
+    let read = stream.read(&mut header);
+    stream.seek(0);
+    header.reverse();
+    let written = stream.write(&header[..read]);
+    stream.flush();
+    written
+}
+
+fn main() {
+    // Create a mock object
+    let mut m = StreamMock::new();
+
+    // Set up expectations for it, in order across all of the traits
+    m.expect_read().called_once().modifying(|buf| {
+        buf.with_mut(|buf| buf.copy_from_slice(&[1, 2, 3, 4]));
+    }).returning(|_| 4);
+    m.then().expect_seek().called_once().with(0).returning(|_| 0);
+    m.then().expect_write().called_once().with(deref(vec![4, 3, 2, 1])).returning(|data| data.with(|data| data.len()));
+    m.then().expect_flush().called_once();
+
+    // Execute test code
+    let mut stream: Box<dyn Stream> = Box::new(m);
+    assert_eq!(patch_header(&mut stream), 4);
+
+    // When the mock object is dropped, its expectations will be evaluated
+}
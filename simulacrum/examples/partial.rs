@@ -0,0 +1,54 @@
+// `create_mock!` can leave a trait's provided methods unmocked, so that their
+// default bodies run instead.
+//
+// Only list the methods that should be mocked. Provided methods that are left
+// out keep the trait's implementation, and any mocked methods they call check
+// their expectations as usual. Provided methods can still be mocked by listing
+// them, like any other method.
+
+extern crate simulacrum;
+
+use simulacrum::*;
+
+trait Catalog {
+    fn price(&self, item: u32) -> u32;
+
+    fn discount(&self) -> u32;
+
+    // Provided helper methods, built on the required ones
+    fn total(&self, items: &[u32]) -> u32 {
+        let sum: u32 = items.iter().map(|&item| self.price(item)).sum();
+        sum - sum * self.discount() / 100
+    }
+
+    fn cheapest(&self, items: &[u32]) -> Option<u32> {
+        items.iter().cloned().min_by_key(|&item| self.price(item))
+    }
+}
+
+create_mock! {
+    impl Catalog for CatalogMock (self) {
+        expect_price("price"):
+        fn price(&self, item: u32) -> u32;
+
+        expect_discount("discount"):
+        fn discount(&self) -> u32;
+
+        // `total()` and `cheapest()` are left out, so they aren't mocked
+    }
+}
+
+fn main() {
+    // Create a mock object
+    let mut m = CatalogMock::new();
+
+    // Set up expectations for the required methods
+    m.expect_price().called_times(5).returning(|item| item * 10);
+    m.expect_discount().called_once().returning(|_| 50);
+
+    // Execute test code, which runs the default bodies
+    assert_eq!(m.total(&[1, 2, 3]), 30);
+    assert_eq!(m.cheapest(&[3, 1]), Some(1));
+
+    // When the mock object is dropped, its expectations will be evaluated
+}
@@ -0,0 +1,83 @@
+// `create_mock!` can mock methods that take `self` by value, or through a
+// `Box`, `Rc`, `Arc` or `Pin`.
+//
+// A mock object that is consumed by one of these methods is dropped inside the
+// call, so its expectations are verified right then.
+
+extern crate simulacrum;
+
+use simulacrum::*;
+
+use std::pin::Pin;
+use std::rc::Rc;
+
+trait Builder {
+    fn header(self, name: &str, value: &str) -> Self;
+
+    fn send(self) -> u16;
+}
+
+trait Request {
+    fn send_boxed(self: Box<Self>) -> u16;
+
+    fn describe(self: Rc<Self>) -> String;
+
+    fn poll_ready(self: Pin<&mut Self>) -> bool;
+}
+
+create_mock! {
+    impl Builder for BuilderMock (self) {
+        expect_header("header"):
+        fn header(self, name: &str, value: &str) -> Self;
+
+        expect_send("send"):
+        fn send(self) -> u16;
+    }
+}
+
+create_mock! {
+    impl Request for RequestMock (self) {
+        expect_send_boxed("send_boxed"):
+        fn send_boxed(self: Box<Self>) -> u16;
+
+        expect_describe("describe"):
+        fn describe(self: Rc<Self>) -> String;
+
+        expect_poll_ready("poll_ready"):
+        fn poll_ready(self: Pin<&mut Self>) -> bool;
+    }
+}
+
+fn main() {
+    // A builder-style method returns the next mock object
+    let mut m = BuilderMock::new();
+    m.expect_header().called_once().with(params!(deref("Accept"), deref("text/plain"))).returning(|_| {
+        let mut next = BuilderMock::new();
+        next.expect_send().called_once().returning(|_| 200);
+        next
+    });
+    assert_eq!(m.header("Accept", "text/plain").send(), 200);
+
+    // Boxed, reference-counted and pinned receivers. `Arc<Self>` works the same
+    // way as `Rc<Self>`.
+    let mut m = RequestMock::new();
+    m.expect_send_boxed().called_once().returning(|_| 204);
+    let boxed: Box<dyn Request> = Box::new(m);
+    assert_eq!(boxed.send_boxed(), 204);
+
+    let mut m = RequestMock::new();
+    m.expect_describe().called_once().returning(|_| "GET /".to_owned());
+    assert_eq!(Rc::new(m).describe(), "GET /");
+
+    let mut m = RequestMock::new();
+    m.expect_poll_ready().called_times(2).returning(|_| true);
+    let mut pinned = Box::pin(m);
+    assert!(pinned.as_mut().poll_ready());
+    assert!(pinned.as_mut().poll_ready());
+
+    // Expectations of a consumed mock object are verified inside the call
+    let mut m = BuilderMock::new();
+    m.expect_send().called_once().returning(|_| 500);
+    m.expect_header().called_never();
+    assert_eq!(m.send(), 500);
+}
@@ -0,0 +1,51 @@
+// `create_mock!` can mock a trait with supertraits, by giving the trait and
+// each of its supertraits their own `impl` section in a `struct` section.
+//
+// All the methods share one set of expectations, and each method's name is
+// prefixed with its trait's name, like "Named::name".
+
+extern crate simulacrum;
+
+use simulacrum::*;
+
+trait Named {
+    fn name(&self) -> String;
+}
+
+trait Service: Named {
+    fn call(&mut self, request: u32) -> u32;
+}
+
+create_mock! {
+    struct ServiceMock (self) {
+        impl Service {
+            expect_call("call"):
+            fn call(&mut self, request: u32) -> u32;
+        }
+
+        impl Named {
+            expect_name("name"):
+            fn name(&self) -> String;
+        }
+    }
+}
+
+// Code under test, which logs the service it calls
+fn handle(service: &mut dyn Service, request: u32) -> String {
+    let response = service.call(request);
+    format!("{} answered {}", service.name(), response)
+}
+
+fn main() {
+    // Create a mock object
+    let mut m = ServiceMock::new();
+
+    // Set up expectations for it
+    m.expect_call().called_once().with(7).returning(|request| request * 2);
+    m.then().expect_name().called_once().returning(|_| "doubler".to_string());
+
+    // Execute test code
+    assert_eq!(handle(&mut m, 7), "doubler answered 14");
+
+    // When the mock object is dropped, its expectations will be evaluated
+}
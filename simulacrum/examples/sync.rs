@@ -0,0 +1,42 @@
+// With the `sync` feature enabled, mock objects are `Send + Sync`, so they
+// can be shared with code that runs on other threads.
+//
+// Run this example with `cargo run --example sync --features sync`.
+
+extern crate simulacrum;
+
+use simulacrum::*;
+
+use std::sync::Arc;
+use std::thread;
+
+trait Counter {
+    fn count(&self, amount: u32) -> u32;
+}
+
+create_mock! {
+    impl Counter for CounterMock (self) {
+        expect_count("count"):
+        fn count(&self, amount: u32) -> u32;
+    }
+}
+
+fn main() {
+    // Create a mock object
+    let mut m = CounterMock::new();
+
+    // Set up expectations for it. Closures must be `Send`.
+    m.expect_count().called_times(4).with(passes(|arg| *arg < 4)).returning(|arg| arg + 1);
+
+    // Share it as a trait object and execute test code on several threads
+    let counter: Arc<dyn Counter + Send + Sync> = Arc::new(m);
+    let threads: Vec<_> = (0..4).map(|i| {
+        let counter = counter.clone();
+        thread::spawn(move || counter.count(i))
+    }).collect();
+    let total: u32 = threads.into_iter().map(|t| t.join().unwrap()).sum();
+    assert_eq!(total, 10);
+
+    // When the last reference to the mock object is dropped, its expectations
+    // will be evaluated
+}
@@ -2,6 +2,28 @@
 //! 
 //! This crate is a facade that just re-exports any crates necessary to both
 //! create and use mock objects in Simulacrum.
+//!
+//! The examples are on the 2015 edition, so they mock methods returning futures
+//! rather than `async fn`s. Crates on a later edition can mock `async fn`s too:
+//!
+//! ```edition2021
+//! use simulacrum::*;
+//!
+//! trait Service {
+//!     async fn fetch(&self, id: u32) -> String;
+//! }
+//!
+//! create_mock! {
+//!     impl Service for ServiceMock (self) {
+//!         expect_fetch("fetch"):
+//!         async fn fetch(&self, id: u32) -> String;
+//!     }
+//! }
+//!
+//! let mut m = ServiceMock::new();
+//! m.expect_fetch().called_once().with(7).returning(|id| format!("item {}", id));
+//! assert_eq!(block_on(m.fetch(7)), "item 7");
+//! ```
 
 extern crate simulacrum_macros;
 extern crate simulacrum_mock;
@@ -0,0 +1,60 @@
+// `#[simulacrum]` can mock traits with associated types and constants.
+//
+// Bind them to concrete values in the attribute's arguments, like you would in
+// any other impl. Methods can then use `Self::Name` for associated types,
+// including in the types of their expectations.
+
+extern crate simulacrum;
+extern crate simulacrum_auto;
+
+use simulacrum_auto::simulacrum;
+
+use std::io;
+
+#[simulacrum(
+    type Error = io::Error;
+    type Key = (u32, String);
+    const NAME: &'static str = "mock";
+)]
+trait Storage {
+    type Error;
+    type Key;
+
+    const NAME: &'static str;
+
+    fn load(&self, key: Self::Key) -> Result<Vec<u8>, Self::Error>;
+
+    fn store(&mut self, key: Self::Key, data: &[u8]) -> Result<(), Self::Error>;
+
+    fn keys(&self) -> Vec<Self::Key>;
+}
+
+// Code under test, which only knows about the trait
+fn backup<S: Storage>(storage: &mut S) -> Result<usize, S::Error> {
+    let mut count = 0;
+    for key in storage.keys() {
+        let data = storage.load(key)?;
+        count += data.len();
+    }
+    Ok(count)
+}
+
+fn main() {
+    // Create a mock object
+    let mut m = StorageMock::new();
+
+    // Set up expectations for it
+    m.expect_keys().called_once().returning(|_| vec![(1, "a".to_owned()), (2, "b".to_owned())]);
+    m.expect_load().called_once().with((1, "a".to_owned())).returning(|_| Ok(vec![1, 2, 3]));
+    m.expect_load().called_once().with((2, "b".to_owned())).returning(|_| {
+        Err(io::Error::new(io::ErrorKind::NotFound, "b is missing"))
+    });
+    m.expect_store().called_never();
+
+    // Execute test code
+    assert_eq!(StorageMock::NAME, "mock");
+    let err = backup(&mut m).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::NotFound);
+
+    // When the mock object is dropped, its expectations will be evaluated
+}
@@ -0,0 +1,66 @@
+// `#[simulacrum]` can mock generic methods, generic traits and traits with
+// lifetime parameters.
+//
+// The mock struct gets the same generic parameters as the trait. Type
+// parameters, of the trait and of its methods, must be `'static` so that
+// expectations can be stored for them. Expectations for a generic method are
+// set up separately for each type it is used with, by passing that type to the
+// `.expect_METHOD_NAME()` method.
+
+extern crate simulacrum;
+extern crate simulacrum_auto;
+
+use simulacrum::*;
+use simulacrum_auto::simulacrum;
+
+use std::fmt::Debug;
+use std::hash::Hash;
+
+#[simulacrum]
+trait Cache {
+    fn put<T: Debug + 'static>(&self, value: T);
+
+    fn make<T: Default + 'static>(&self) -> T;
+}
+
+#[simulacrum]
+trait Repo<K, V> where K: Hash + Eq {
+    fn get(&self, key: K) -> Option<V>;
+
+    fn insert(&mut self, key: K, value: V);
+}
+
+#[simulacrum]
+trait Parser<'a> {
+    fn parse(&self, input: &'a str) -> usize;
+}
+
+fn main() {
+    // Set up expectations for each type the generic methods are used with
+    let mut cache = CacheMock::new();
+    cache.expect_put::<i32>().called_once().with(5);
+    cache.expect_put::<&'static str>().called_times(2);
+    cache.expect_make::<u8>().called_once().returning(|_| 7);
+
+    // Mocks of generic traits are created for specific types
+    let mut repo = RepoMock::<&'static str, i32>::new();
+    repo.expect_insert().called_once().with(params!("a", 1));
+    repo.expect_get().called_once().with("a").returning(|_| Some(1));
+
+    let mut parser = ParserMock::new();
+    parser.expect_parse().called_once().with(deref("12 + 3")).returning(|input| input.with(|input| input.len()));
+
+    // Execute test code
+    cache.put(5);
+    cache.put("hello");
+    cache.put("world");
+    assert_eq!(cache.make::<u8>(), 7);
+
+    repo.insert("a", 1);
+    assert_eq!(repo.get("a"), Some(1));
+
+    let input = String::from("12 + 3");
+    assert_eq!(parser.parse(&input), 6);
+
+    // When the mock objects are dropped, their expectations will be evaluated
+}
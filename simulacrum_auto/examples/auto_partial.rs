@@ -0,0 +1,45 @@
+// `#[simulacrum]` can leave a trait's provided methods unmocked, so that their
+// default bodies run instead.
+//
+// Mark those methods with `#[simulacrum(default)]`. Any mocked methods they
+// call check their expectations as usual. Provided methods without the marker
+// are mocked like any other method.
+
+extern crate simulacrum;
+extern crate simulacrum_auto;
+
+use simulacrum_auto::simulacrum;
+
+#[simulacrum]
+trait Catalog {
+    fn price(&self, item: u32) -> u32;
+
+    fn discount(&self) -> u32;
+
+    // Provided helper methods, built on the required ones
+    #[simulacrum(default)]
+    fn total(&self, items: &[u32]) -> u32 {
+        let sum: u32 = items.iter().map(|&item| self.price(item)).sum();
+        sum - sum * self.discount() / 100
+    }
+
+    #[simulacrum(default)]
+    fn cheapest(&self, items: &[u32]) -> Option<u32> {
+        items.iter().cloned().min_by_key(|&item| self.price(item))
+    }
+}
+
+fn main() {
+    // Create a mock object
+    let mut m = CatalogMock::new();
+
+    // Set up expectations for the required methods
+    m.expect_price().called_times(5).returning(|item| item * 10);
+    m.expect_discount().called_once().returning(|_| 50);
+
+    // Execute test code, which runs the default bodies
+    assert_eq!(m.total(&[1, 2, 3]), 30);
+    assert_eq!(m.cheapest(&[3, 1]), Some(1));
+
+    // When the mock object is dropped, its expectations will be evaluated
+}
@@ -0,0 +1,46 @@
+// `#[simulacrum]` can mock functions that don't take `self`, like
+// constructors.
+//
+// Since there is no mock object to hold their expectations, create one with
+// `with_statics()` instead of `new()`. Its expectations are then also used by
+// the mock type's functions without `self` on the current thread, until it is
+// dropped and they are verified.
+
+extern crate simulacrum;
+extern crate simulacrum_auto;
+
+use simulacrum::*;
+use simulacrum_auto::simulacrum;
+
+#[simulacrum]
+trait Connection {
+    fn connect(host: &str, port: u16) -> Self;
+
+    fn default_port() -> u16;
+
+    fn send(&mut self, data: &[u8]) -> usize;
+}
+
+// Code under test, which creates its own connection
+fn upload<C: Connection>(host: &str, data: &[u8]) -> usize {
+    let mut connection = C::connect(host, C::default_port());
+    connection.send(data)
+}
+
+fn main() {
+    // Create a mock object for the functions without `self`
+    let mut statics = ConnectionMock::with_statics();
+
+    // Set up expectations for them, including the connection they return
+    statics.expect_default_port().called_once().returning(|_| 8080);
+    statics.expect_connect().called_once().with(params!(deref("example.com"), 8080)).returning(|_| {
+        let mut connection = ConnectionMock::new();
+        connection.expect_send().called_once().returning(|data| data.with(|data| data.len()));
+        connection
+    });
+
+    // Execute test code
+    assert_eq!(upload::<ConnectionMock>("example.com", b"hello"), 5);
+
+    // When the mock objects are dropped, their expectations will be evaluated
+}
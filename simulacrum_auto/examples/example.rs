@@ -1,5 +1,3 @@
-#![feature(proc_macro)]
-
 extern crate simulacrum;
 extern crate simulacrum_auto;
 
@@ -40,7 +38,7 @@ fn main() {
     m.expect_boop().called_times(2);
     m.expect_store().called_once().with(deref(777));
     m.expect_toggle().called_once().with(deref(true))
-                                   .modifying(|&mut arg| { unsafe { *arg = false } });
+                                   .modifying(|arg| arg.with_mut(|arg| *arg = false));
 
     // Execute test code
     m.foo();
@@ -51,7 +49,7 @@ fn main() {
     m.store(&777);
     let mut b = true;
     m.toggle(&mut b);
-    assert_eq!(b, false);
+    assert!(!b);
 
     // When the Expectations struct is dropped, each of its expectations will be evaluated
 }
\ No newline at end of file
@@ -0,0 +1,77 @@
+// `#[simulacrum]` takes options that change how the mock struct is generated.
+//
+// - `name = "FakeStore"` names the mock, instead of `StoreMock`.
+// - `vis = "pub(crate)"` sets the visibility of the mock and its methods,
+//   which are `pub` otherwise.
+// - `cfg_test` only generates the mock when building tests, so that it stays
+//   out of other builds.
+// - `derive(Debug)` derives `Debug` for the mock struct. Other traits can't be
+//   derived for mocks.
+//
+// Run `cargo test --example options` to see the mock used.
+
+extern crate simulacrum;
+extern crate simulacrum_auto;
+
+use simulacrum_auto::simulacrum;
+
+#[simulacrum(name = "FakeStore", vis = "pub(crate)", cfg_test, derive(Debug))]
+trait Store {
+    fn get(&self, key: &str) -> Option<i32>;
+
+    fn set(&mut self, key: &str, value: i32);
+}
+
+// The real implementation
+#[derive(Default)]
+struct MemoryStore {
+    values: Vec<(String, i32)>
+}
+
+impl Store for MemoryStore {
+    fn get(&self, key: &str) -> Option<i32> {
+        self.values.iter().find(|(k, _)| k == key).map(|&(_, v)| v)
+    }
+
+    fn set(&mut self, key: &str, value: i32) {
+        self.values.retain(|(k, _)| k != key);
+        self.values.push((key.to_owned(), value));
+    }
+}
+
+// Code under test, which only knows about the trait
+fn increment<S: Store>(store: &mut S, key: &str) -> i32 {
+    let value = store.get(key).unwrap_or(0) + 1;
+    store.set(key, value);
+    value
+}
+
+fn main() {
+    // Not a test, so there is no mock here
+    let mut store = MemoryStore::default();
+    assert_eq!(increment(&mut store, "visits"), 1);
+    assert_eq!(increment(&mut store, "visits"), 2);
+}
+
+#[cfg(test)]
+mod tests {
+    use simulacrum::*;
+
+    use super::*;
+
+    #[test]
+    fn test_increment() {
+        // Set up expectations for the mock, which has the name it was given
+        let mut m = FakeStore::new();
+        m.expect_get().called_once().with(deref("visits")).returning(|_| Some(41));
+        m.expect_set().called_once().with(params!(deref("visits"), 42));
+
+        // Execute test code
+        assert_eq!(increment(&mut m, "visits"), 42);
+
+        // Derived traits work like on any other struct
+        assert!(format!("{:?}", m).starts_with("FakeStore { e: Expectations { mock_name: \"FakeStore\" }"));
+
+        // When the mock object is dropped, its expectations will be evaluated
+    }
+}
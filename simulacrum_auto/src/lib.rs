@@ -1,471 +1,1511 @@
-#![feature(proc_macro)]
-#![recursion_limit="128"]
-
              extern crate proc_macro;
+             extern crate proc_macro2;
 #[macro_use] extern crate quote;
-             extern crate simulacrum;
-             extern crate syn;
+#[macro_use] extern crate syn;
 
 use proc_macro::TokenStream;
-use quote::ToTokens;
+use proc_macro2::TokenStream as TokenStream2;
+use syn::{Error, FnArg, Ident, Result, Type};
+use syn::parse::{Parse, ParseStream};
+use syn::punctuated::Punctuated;
+use syn::visit_mut::VisitMut;
+
+/// Create a mock object for the trait or inherent impl this is attached to.
+///
+/// The mock struct is named after the trait or type, like `CoolTraitMock`, and
+/// has an `expect_METHOD_NAME()` method for each of its methods. Items that
+/// can't be mocked are reported as compile errors.
+///
+/// Associated types and constants of a trait are bound in the arguments, like
+/// `#[simulacrum(type Error = io::Error;)]`. Supertraits are mocked too if
+/// their definitions are given, like
+/// `#[simulacrum(supertraits(Named { fn name(&self) -> String; }))]`.
+///
+/// Methods marked with `#[simulacrum(default)]` aren't mocked, so that their
+/// default bodies run instead.
+///
+/// The mock can be renamed with `name = "FakeStore"`, and given a visibility
+/// other than `pub` with `vis = "pub(crate)"`. `cfg_test` only generates it
+/// when building tests, and `derive(Debug)` derives `Debug` for it.
+///
+/// `async fn`s are mocked like methods returning `impl Future`:
+///
+/// ```edition2021
+/// use simulacrum::*;
+/// use simulacrum_auto::simulacrum;
+///
+/// #[simulacrum]
+/// trait Service {
+///     async fn fetch(&self, id: u32) -> String;
+/// }
+///
+/// let mut m = ServiceMock::new();
+/// m.expect_fetch().called_once().with(7).returning(|id| format!("item {}", id));
+/// assert_eq!(block_on(m.fetch(7)), "item 7");
+/// ```
+#[proc_macro_attribute]
+pub fn simulacrum(args: TokenStream, input: TokenStream) -> TokenStream {
+    let input = TokenStream2::from(input);
+    match simulacrum_internal(args.into(), input.clone()) {
+        Ok(output) => output.into(),
+        Err(err) => {
+            // Keep the original item, so that the error is the only one reported
+            let item = without_default_markers(input);
+            // `to_compile_error()` names `::core`, which 2015 edition crates
+            // can't use, so the errors are emitted here instead
+            let errors = err.into_iter().map(|err| {
+                let message = err.to_string();
+                quote_spanned!(err.span()=> compile_error!(#message);)
+            });
+            quote!(#item #(#errors)*).into()
+        }
+    }
+}
+
+fn simulacrum_internal(args: TokenStream2, input: TokenStream2) -> Result<TokenStream2> {
+    let args: Args = syn::parse2(args)?;
+    match syn::parse2(input)? {
+        syn::Item::Trait(item) => simulacrum_trait(&args, item),
+        syn::Item::Impl(item) => simulacrum_impl(&args, item),
+        item => Err(Error::new_spanned(item, "#[simulacrum] can only mock traits and inherent impl blocks."))
+    }
+}
+
+// The arguments given to the attribute.
+#[derive(Default)]
+struct Args {
+    // Bindings for associated types and constants, like `type Error = io::Error;`
+    bindings: Vec<syn::ImplItem>,
+    // Definitions of supertraits, like `supertraits(Named { ... })`
+    supertraits: Vec<Supertrait>,
+    // The mock struct's name, instead of the mocked name followed by `Mock`
+    name: Option<Ident>,
+    // The visibility of the mock struct and its methods, instead of `pub`
+    vis: Option<syn::Visibility>,
+    // Only generate the mock when building tests
+    cfg_test: bool,
+    // Traits to derive for the mock struct
+    derives: Vec<syn::Path>
+}
+
+impl Args {
+    fn mock_name(&self, ident: &Ident) -> Ident {
+        match self.name {
+            Some(ref name) => name.clone(),
+            None => format_ident!("{}Mock", ident)
+        }
+    }
 
-use std::str::FromStr;
+    fn vis(&self) -> TokenStream2 {
+        match self.vis {
+            Some(ref vis) => quote!(#vis),
+            None => quote!(pub)
+        }
+    }
 
+    // An attribute for every item of the mock, to leave it out of other builds
+    fn cfg(&self) -> TokenStream2 {
+        if self.cfg_test {
+            quote!(#[cfg(test)])
+        } else {
+            quote!()
+        }
+    }
+}
+
+struct Supertrait {
+    ident: Ident,
+    items: Vec<syn::TraitItem>
+}
+
+impl Parse for Args {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let mut args = Args::default();
+        while !input.is_empty() {
+            if input.peek(Token![type]) || input.peek(Token![const]) {
+                args.bindings.push(input.parse()?);
+                continue;
+            }
+
+            let option: Ident = input.parse()?;
+            if option == "supertraits" {
+                let definitions;
+                parenthesized!(definitions in input);
+                while !definitions.is_empty() {
+                    let ident = definitions.parse()?;
+                    let body;
+                    braced!(body in definitions);
+                    let mut items = Vec::new();
+                    while !body.is_empty() {
+                        items.push(body.parse()?);
+                    }
+                    args.supertraits.push(Supertrait { ident, items });
+                    if definitions.peek(Token![,]) {
+                        definitions.parse::<Token![,]>()?;
+                    }
+                }
+            } else if option == "name" {
+                input.parse::<Token![=]>()?;
+                let name: syn::LitStr = input.parse()?;
+                args.name = Some(name.parse().map_err(|_| {
+                    Error::new_spanned(&name, "`name` needs to be an identifier, like `name = \"FakeStore\"`.")
+                })?);
+            } else if option == "vis" {
+                input.parse::<Token![=]>()?;
+                let vis: syn::LitStr = input.parse()?;
+                args.vis = Some(vis.parse().map_err(|_| {
+                    Error::new_spanned(&vis, "`vis` needs to be a visibility, like `vis = \"pub(crate)\"`.")
+                })?);
+            } else if option == "cfg_test" {
+                args.cfg_test = true;
+            } else if option == "derive" {
+                let derives;
+                parenthesized!(derives in input);
+                for derive in Punctuated::<syn::Path, Token![,]>::parse_terminated(&derives)? {
+                    // Other traits clash with the generated `Default` impl, or
+                    // can't be derived for the `Expectations` in the mock
+                    if !derive.is_ident("Debug") {
+                        return Err(Error::new_spanned(&derive, "Only `Debug` can be derived for mocks."));
+                    }
+                    args.derives.push(derive);
+                }
+            } else {
+                return Err(Error::new(option.span(), format!("Unknown argument `{}`.", option)));
+            }
+            if input.peek(Token![,]) {
+                input.parse::<Token![,]>()?;
+            }
+        }
+        Ok(args)
+    }
+}
+
+// A method of the mock, along with the types of its expectations.
 struct Method {
-    ident: syn::Ident,
-    original_item: syn::TraitItem,
-    sig: syn::MethodSig
+    // The method's signature, with every parameter named
+    sig: syn::Signature,
+    key: String,
+    expect_name: Ident,
+    // The parameter and return types, with `Self::Name` qualified by the trait
+    inputs: TokenStream2,
+    output: TokenStream2,
+    returns: Returns
 }
 
-#[proc_macro_attribute]
-pub fn simulacrum(_args: TokenStream, input: TokenStream) -> TokenStream {
-    // Generate the Rust code string to use as the output
-    let output = simulacrum_internal(&input.to_string());
+#[derive(PartialEq)]
+enum Returns {
+    Value,
+    // `async fn`
+    Async,
+    // `impl Future<Output = T>`
+    Future,
+    // `Pin<Box<dyn Future<Output = T>>>`
+    BoxedFuture
+}
 
-    // Turn that Rust back into a token stream
-    TokenStream::from_str(output.as_str()).unwrap()
+// The methods and associated items that the mock implements for one trait.
+struct Section {
+    path: syn::Path,
+    items: Vec<syn::ImplItem>,
+    methods: Vec<Method>
 }
 
-fn simulacrum_internal(input: &str) -> quote::Tokens {
-    // Generate the AST from the token stream we were given
-    let item = syn::parse_item(&input.to_string()).unwrap();
+// Methods that every mock object has, which inherent methods can't replace.
+const MOCK_METHODS: &[&str] = &[
+    "with_statics", "then", "strict", "verify_all", "checkpoint",
+    "record_calls", "record_params", "call_log", "calls"
+];
 
-    // Generate Mock struct name
-    let ident = &item.ident;
-    let name = quote! { #ident };
-    let name = syn::Ident::new(format!("{}Mock", name.as_str()));
+// Traits that mock objects implement without a definition.
+const MARKER_TRAITS: &[&str] = &["Send", "Sync", "Sized", "Unpin", "Any"];
 
-    // Get method information
-    let trait_items = get_trait_items(&item);
-    let methods = gather_trait_methods(&trait_items);
+fn simulacrum_trait(args: &Args, mut item: syn::ItemTrait) -> Result<TokenStream2> {
+    // Generate Mock struct name
+    let ident = item.ident.clone();
+    let name = args.mock_name(&ident);
+
+    // The Mock struct shares the trait's generic parameters
+    check_generics(&item.generics)?;
+    let (_, ty_generics, _) = item.generics.split_for_impl();
+
+    // Every supertrait needs a definition to be mocked from
+    for bound in &item.supertraits {
+        if let syn::TypeParamBound::Trait(ref bound) = *bound {
+            let supertrait = &bound.path.segments.last().unwrap().ident;
+            let is_marker = MARKER_TRAITS.iter().any(|marker| supertrait == marker);
+            let is_defined = args.supertraits.iter().any(|definition| definition.ident == *supertrait);
+            if !is_marker && !is_defined {
+                return Err(Error::new_spanned(bound, format!(
+                    "Supertrait `{}` needs a definition, like `#[simulacrum(supertraits({} {{ ... }}))]`.",
+                    supertrait, supertrait
+                )));
+            }
+        }
+    }
 
-    // Generate what is required for the create_mock! macro
-    let annotations = generate_annotations(&methods);
-    let original_methods = gather_original_methods(&methods);
+    // Gather each trait's methods, namespacing their names if there are several
+    let namespaced = !args.supertraits.is_empty();
+    let mut used_bindings = Vec::new();
+    let mut sections = vec![gather_section(
+        parse_quote!(#ident #ty_generics), &item.items, args, namespaced, &mut used_bindings
+    )?];
+    for supertrait in &args.supertraits {
+        let super_ident = &supertrait.ident;
+        sections.push(gather_section(
+            parse_quote!(#super_ident), &supertrait.items, args, namespaced, &mut used_bindings
+        )?);
+    }
+    for binding in &args.bindings {
+        let binding_ident = match *binding {
+            syn::ImplItem::Type(ref binding) => &binding.ident,
+            syn::ImplItem::Const(ref binding) => &binding.ident,
+            _ => unreachable!()
+        };
+        if !used_bindings.contains(binding_ident) {
+            return Err(Error::new_spanned(binding_ident, format!(
+                "`{}` isn't an associated type or constant of `{}`.", binding_ident, ident
+            )));
+        }
+    }
+    check_expect_names(sections.iter().flat_map(|section| section.methods.iter()))?;
 
     // Generate fn expect_blah() -> Method methods
-    // let expects = generate_expects(&methods);
+    let methods: Vec<&Method> = sections.iter().flat_map(|section| section.methods.iter()).collect();
+    let vis = args.vis();
+    let expects = generate_expects(&methods, &vis);
+    let mut mock_struct = generate_struct(&name, &item.generics, expects, args);
+    mock_struct.extend(generate_method_keys(&name, &item.generics, methods.iter().cloned(), args));
+
+    // Generate blah() stub methods in an impl of each trait
+    let generics = item.generics.clone();
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    let type_params: Vec<&Ident> = generics.type_params().map(|param| &param.ident).collect();
+    let predicates = where_clause.map(|where_clause| &where_clause.predicates);
+    let unsafety = &item.unsafety;
+    let cfg = args.cfg();
+    let mut impls = Vec::new();
+    for section in &sections {
+        let path = &section.path;
+        let items = &section.items;
+        let stubs = generate_stubs(&section.methods, quote!());
+        impls.push(quote! {
+            #cfg
+            #unsafety impl #impl_generics #path for #name #ty_generics where
+                #(#type_params: 'static,)*
+                #predicates
+            {
+                #(#items)*
+
+                #(#stubs)*
+            }
+        });
+    }
+
+    // Generate delegate_to(), which forwards calls to an implementation of
+    // every trait
+    let bounds: Vec<syn::Path> = sections.iter().map(bind_associated_types).collect();
+    let mut delegates = Vec::new();
+    for section in &sections {
+        for method in &section.methods {
+            if let Some(delegate) = generate_delegate(method, &section.path) {
+                delegates.push(delegate);
+            }
+        }
+    }
 
-    // Generate blah() stub methods
-    // let stubs = generate_stubs(&methods);
+    // The trait itself is output without the markers for default methods
+    remove_default_markers(&mut item);
 
-    let output = quote! {
+    Ok(quote! {
         #item
 
-        create_mock! {
-            impl #ident for #name (self) {
-                #(
-                    #annotations
-                    #original_methods
-                )*
+        #mock_struct
+
+        #(#impls)*
+
+        #cfg
+        #[allow(dead_code)]
+        impl #impl_generics #name #ty_generics where
+            #(#type_params: 'static,)*
+            #predicates
+        {
+            /// Forward calls to `inner` whenever no expectation specifies a
+            /// return value with `returning()`. Expectations are still checked.
+            ///
+            /// Only methods taking `&self` or `&mut self` are forwarded. Generic
+            /// methods, `async` methods and methods returning futures, methods
+            /// taking `self` by value or through a smart pointer, methods with
+            /// params borrowed for a named lifetime other than `'static`, and
+            /// functions without `self` aren't, so they still need
+            /// `returning()` if they return a value.
+            #vis fn delegate_to<__Inner>(&mut self, inner: __Inner) -> &mut Self where
+                __Inner: #(#bounds +)* ::simulacrum::MaybeSend + 'static
+            {
+                let __inner = ::std::sync::Arc::new(::std::sync::Mutex::new(inner));
+                #(#delegates)*
+                self
             }
         }
+    })
+}
+
+// Whether the function returns `Self` or the type it belongs to.
+fn returns_self(sig: &syn::Signature, ident: &Ident) -> bool {
+    match sig.output {
+        syn::ReturnType::Type(_, ref ty) => match **ty {
+            Type::Path(ref ty) => ty.qself.is_none() && (ty.path.is_ident("Self") || ty.path.is_ident(ident)),
+            _ => false
+        },
+        syn::ReturnType::Default => false
+    }
+}
+
+fn simulacrum_impl(args: &Args, item: syn::ItemImpl) -> Result<TokenStream2> {
+    if let Some((_, ref path, _)) = item.trait_ {
+        return Err(Error::new_spanned(path, "#[simulacrum] can't mock trait impls. Put it on the trait instead."));
+    }
+    if let Some(binding) = args.bindings.first() {
+        return Err(Error::new_spanned(binding, "Associated items can only be bound when mocking a trait."));
+    }
+    if let Some(supertrait) = args.supertraits.first() {
+        return Err(Error::new_spanned(&supertrait.ident, "Supertraits can only be given when mocking a trait."));
+    }
+    if !item.generics.params.is_empty() {
+        return Err(Error::new_spanned(&item.generics, "#[simulacrum] can't mock generic impl blocks."));
+    }
+
+    // Generate Mock struct name
+    let ident = match *item.self_ty {
+        Type::Path(ref ty) if ty.qself.is_none() => &ty.path.segments.last().unwrap().ident,
+        ref ty => return Err(Error::new_spanned(ty, "#[simulacrum] can only mock impl blocks of named types."))
     };
+    let name = args.mock_name(ident);
+
+    // Get method information, keeping constants as they are
+    let mut methods = Vec::new();
+    let mut consts = Vec::new();
+    for impl_item in &item.items {
+        match *impl_item {
+            // The mock struct has its own `new()`, which stands in for a
+            // `new()` without parameters. Any other one would clash with it.
+            syn::ImplItem::Fn(ref method) if method.sig.ident == "new" => {
+                if !method.sig.inputs.is_empty() || !returns_self(&method.sig, ident) {
+                    return Err(Error::new_spanned(&method.sig, format!(
+                        "`new` can't be mocked, since `{}` has its own `new()` that takes no parameters and returns `Self`. \
+                         Move this one to another impl block, or give it another name.", name
+                    )));
+                }
+            },
+            syn::ImplItem::Fn(ref method) => {
+                let method_ident = &method.sig.ident;
+                if MOCK_METHODS.iter().any(|mock_method| method_ident == mock_method) {
+                    return Err(Error::new_spanned(method_ident, format!(
+                        "`{}` can't be mocked, since mock objects have a method with the same name.", method_ident
+                    )));
+                }
+                methods.push(gather_method(&method.sig, method_ident.to_string(), None)?);
+            },
+            syn::ImplItem::Const(ref constant) => consts.push(constant),
+            ref other => {
+                return Err(Error::new_spanned(other, "#[simulacrum] can only mock the methods and constants of impl blocks."));
+            }
+        }
+    }
+    check_expect_names(methods.iter())?;
+
+    // Generate expect_blah() methods and blah() stubs on the mock itself
+    let vis = args.vis();
+    let method_refs: Vec<&Method> = methods.iter().collect();
+    let mut inherent = generate_expects(&method_refs, &vis);
+    let stubs = generate_stubs(&methods, vis);
+    inherent.extend(quote! {
+        #(#consts)*
+
+        #(#stubs)*
+    });
+    let mut mock_struct = generate_struct(&name, &syn::Generics::default(), inherent, args);
+    mock_struct.extend(generate_method_keys(&name, &syn::Generics::default(), methods.iter(), args));
+
+    Ok(quote! {
+        #item
 
-    output
+        #mock_struct
+    })
 }
 
-fn get_trait_items(item: &syn::Item) -> Vec<syn::TraitItem> {
-    match item.node {
-        syn::ItemKind::Trait(_unsafety, ref _generics, ref _ty_param_bound, ref items) => {
-            items.clone()
-        },
-        _ => vec![].clone()
+fn check_generics(generics: &syn::Generics) -> Result<()> {
+    for param in &generics.params {
+        if let syn::GenericParam::Const(ref param) = *param {
+            return Err(Error::new_spanned(param, "#[simulacrum] can't mock traits with const generic parameters."));
+        }
     }
+    Ok(())
 }
 
-fn gather_trait_methods(trait_items: &Vec<syn::TraitItem>) -> Vec<Method> {
-    let mut result = Vec::new();
-    for item in trait_items {
-        match item.node {
-            syn::TraitItemKind::Method(ref sig, _) => {
-                let m = Method {
-                    ident: item.ident.clone(),
-                    original_item: item.clone(),
-                    sig: sig.clone()
+// Gather the methods of a trait, and bind its associated types and constants
+// to what the attribute gives them.
+fn gather_section(path: syn::Path, trait_items: &[syn::TraitItem], args: &Args, namespaced: bool, used_bindings: &mut Vec<Ident>) -> Result<Section> {
+    let trait_ident = path.segments.last().unwrap().ident.clone();
+    let mut items = Vec::new();
+    let mut methods = Vec::new();
+    for trait_item in trait_items {
+        match *trait_item {
+            syn::TraitItem::Fn(ref method) => {
+                // Methods marked to keep their default body aren't mocked
+                if is_default_marked(&method.attrs)? {
+                    if method.default.is_none() {
+                        return Err(Error::new_spanned(&method.sig.ident, format!(
+                            "Method `{}` is marked `#[simulacrum(default)]`, but has no default body.", method.sig.ident
+                        )));
+                    }
+                    continue;
+                }
+                let key = if namespaced {
+                    format!("{}::{}", trait_ident, method.sig.ident)
+                } else {
+                    method.sig.ident.to_string()
                 };
-                result.push(m);
+                methods.push(gather_method(&method.sig, key, Some(&path))?);
+            },
+            syn::TraitItem::Type(ref associated) => {
+                match find_binding(&args.bindings, &associated.ident) {
+                    Some(binding @ &syn::ImplItem::Type(_)) => {
+                        items.push(binding.clone());
+                        used_bindings.push(associated.ident.clone());
+                    },
+                    _ => {
+                        return Err(Error::new_spanned(&associated.ident, format!(
+                            "Associated type `{}` needs a binding, like `#[simulacrum(type {} = ...;)]`.",
+                            associated.ident, associated.ident
+                        )));
+                    }
+                }
+            },
+            syn::TraitItem::Const(ref associated) => {
+                match find_binding(&args.bindings, &associated.ident) {
+                    Some(binding @ &syn::ImplItem::Const(_)) => {
+                        items.push(binding.clone());
+                        used_bindings.push(associated.ident.clone());
+                    },
+                    // Constants with a default can keep it
+                    _ if associated.default.is_some() => { },
+                    _ => {
+                        return Err(Error::new_spanned(&associated.ident, format!(
+                            "Associated constant `{}` needs a binding, like `#[simulacrum(const {}: ... = ...;)]`.",
+                            associated.ident, associated.ident
+                        )));
+                    }
+                }
             },
-            _ => { }
+            ref other => {
+                return Err(Error::new_spanned(other, "#[simulacrum] can only mock the methods, types and constants of traits."));
+            }
         }
     }
-    result
+    Ok(Section { path, items, methods })
 }
 
-fn generate_annotations(methods: &Vec<Method>) -> Vec<quote::Tokens> {
-    let mut result = Vec::new();
+fn find_binding<'a>(bindings: &'a [syn::ImplItem], ident: &Ident) -> Option<&'a syn::ImplItem> {
+    bindings.iter().find(|binding| match **binding {
+        syn::ImplItem::Type(ref binding) => binding.ident == *ident,
+        syn::ImplItem::Const(ref binding) => binding.ident == *ident,
+        _ => false
+    })
+}
+
+fn gather_method(sig: &syn::Signature, key: String, trait_path: Option<&syn::Path>) -> Result<Method> {
+    if let Some(ref variadic) = sig.variadic {
+        return Err(Error::new_spanned(variadic, "#[simulacrum] can't mock variadic functions."));
+    }
+
+    // Name every parameter, so that the stub can pass them on
+    let mut sig = sig.clone();
+    for (i, arg) in sig.inputs.iter_mut().enumerate() {
+        if let FnArg::Typed(ref mut arg) = *arg {
+            if let Type::ImplTrait(_) = *arg.ty {
+                return Err(Error::new_spanned(&arg.ty, "`impl Trait` parameters can't be mocked. Use a generic parameter instead."));
+            }
+            let param_ident = match *arg.pat {
+                syn::Pat::Ident(ref pat) if pat.by_ref.is_none() && pat.subpat.is_none() => pat.ident.clone(),
+                _ => format_ident!("__arg{}", i)
+            };
+            *arg.pat = parse_quote!(#param_ident);
+        }
+    }
+
+    // The expect methods live outside of the trait's impl, so `Self::Name` is
+    // qualified with the trait there
+    let mut qualified = sig.clone();
+    if let Some(trait_path) = trait_path {
+        QualifySelf(trait_path).visit_signature_mut(&mut qualified);
+    }
+
+    let (returns, output) = generate_output_type(&qualified)?;
+    Ok(Method {
+        key,
+        expect_name: expectify_method_name(&sig.ident),
+        inputs: generate_input_tuple(&qualified.inputs),
+        output,
+        returns,
+        sig
+    })
+}
+
+// Qualify `Self::Name` as `<Self as Trait>::Name`.
+struct QualifySelf<'a>(&'a syn::Path);
+
+impl<'a> VisitMut for QualifySelf<'a> {
+    fn visit_type_path_mut(&mut self, ty: &mut syn::TypePath) {
+        syn::visit_mut::visit_type_path_mut(self, ty);
+        if ty.qself.is_none() && ty.path.leading_colon.is_none() && ty.path.segments.len() > 1 && ty.path.segments[0].ident == "Self" {
+            let trait_path = self.0;
+            let rest: Vec<&syn::PathSegment> = ty.path.segments.iter().skip(1).collect();
+            *ty = parse_quote!(<Self as #trait_path>::#(#rest)::*);
+        }
+    }
+}
+
+fn expectify_method_name(ident: &Ident) -> Ident {
+    format_ident!("expect_{}", ident)
+}
+
+// Make sure that no two methods get the same expect method.
+fn check_expect_names<'a, I: Iterator<Item = &'a Method>>(methods: I) -> Result<()> {
+    let mut seen: Vec<&Ident> = Vec::new();
     for method in methods {
-        let ident = &method.ident;
-        let ident_tokens = quote!{ #ident };
-        let ident_str = ident_tokens.as_str();
-        let name = expectify_method_name(ident);
-        // let otype = generate_output_type(&method.sig.decl.output);
-        // let ituple = generate_input_tuple(&method.sig.decl.inputs);
-        let annotation = quote! {
-            #name(#ident_str):
+        if seen.contains(&&method.expect_name) {
+            return Err(Error::new_spanned(&method.sig.ident, format!(
+                "Methods of different traits named `{}` would both get `{}()`.", method.sig.ident, method.expect_name
+            )));
+        }
+        seen.push(&method.expect_name);
+    }
+    Ok(())
+}
+
+fn generate_output_type(sig: &syn::Signature) -> Result<(Returns, TokenStream2)> {
+    let ty = match sig.output {
+        syn::ReturnType::Default if sig.asyncness.is_some() => return Ok((Returns::Async, quote!(()))),
+        syn::ReturnType::Default => return Ok((Returns::Value, quote!(()))),
+        syn::ReturnType::Type(_, ref ty) => &**ty
+    };
+    let (returns, output) = if sig.asyncness.is_some() {
+        (Returns::Async, ty)
+    } else {
+        match *ty {
+            Type::ImplTrait(ref impl_trait) => match future_output(&impl_trait.bounds) {
+                Some(output) => (Returns::Future, output),
+                None => return Err(Error::new_spanned(ty, "`impl Trait` return types can't be mocked, except for futures."))
+            },
+            _ => match boxed_future_output(ty) {
+                Some(output) => (Returns::BoxedFuture, output),
+                None => (Returns::Value, ty)
+            }
+        }
+    };
+    let mut borrowed = FindBorrowed(None);
+    borrowed.visit_type_mut(&mut output.clone());
+    match borrowed.0 {
+        Some(err) => Err(err),
+        None => Ok((returns, quote!(#output)))
+    }
+}
+
+// Find the first part of a return type that borrows for a lifetime other than
+// `'static`, elided or not. Expectations can only store `'static` values.
+struct FindBorrowed(Option<Error>);
+
+impl FindBorrowed {
+    fn found<T: quote::ToTokens>(&mut self, tokens: T) {
+        if self.0.is_none() {
+            self.0 = Some(Error::new_spanned(tokens, "Return types borrowing for lifetimes other than `'static` can't be mocked."));
+        }
+    }
+}
+
+impl VisitMut for FindBorrowed {
+    fn visit_type_reference_mut(&mut self, reference: &mut syn::TypeReference) {
+        match reference.lifetime {
+            Some(ref lifetime) if lifetime.ident == "static" => {},
+            _ => self.found(&*reference)
+        }
+        syn::visit_mut::visit_type_reference_mut(self, reference);
+    }
+
+    fn visit_lifetime_mut(&mut self, lifetime: &mut syn::Lifetime) {
+        if lifetime.ident != "static" {
+            self.found(&*lifetime);
+        }
+    }
+
+    // Params of function types, like `Box<dyn Fn(&str)>`, can borrow for any
+    // lifetime.
+    fn visit_type_bare_fn_mut(&mut self, _: &mut syn::TypeBareFn) {}
+
+    fn visit_parenthesized_generic_arguments_mut(&mut self, _: &mut syn::ParenthesizedGenericArguments) {}
+}
+
+// Get `T` from `Pin<Box<dyn Future<Output = T>>>`.
+fn boxed_future_output(ty: &Type) -> Option<&Type> {
+    let boxed = single_type_argument(ty, "Pin")?;
+    match single_type_argument(boxed, "Box") {
+        Some(Type::TraitObject(object)) => future_output(&object.bounds),
+        _ => None
+    }
+}
+
+// Get `T` from `Name<T>`.
+fn single_type_argument<'a>(ty: &'a Type, name: &str) -> Option<&'a Type> {
+    let segment = match *ty {
+        Type::Path(ref ty) if ty.qself.is_none() => ty.path.segments.last().unwrap(),
+        _ => return None
+    };
+    if segment.ident != name {
+        return None;
+    }
+    match segment.arguments {
+        syn::PathArguments::AngleBracketed(ref arguments) if arguments.args.len() == 1 => match arguments.args[0] {
+            syn::GenericArgument::Type(ref ty) => Some(ty),
+            _ => None
+        },
+        _ => None
+    }
+}
+
+// Get `T` from bounds like `Future<Output = T> + Send`.
+fn future_output(bounds: &Punctuated<syn::TypeParamBound, Token![+]>) -> Option<&Type> {
+    for bound in bounds {
+        let segment = match *bound {
+            syn::TypeParamBound::Trait(ref bound) => bound.path.segments.last().unwrap(),
+            _ => continue
         };
-        result.push(annotation);
+        if segment.ident != "Future" {
+            continue;
+        }
+        if let syn::PathArguments::AngleBracketed(ref arguments) = segment.arguments {
+            for argument in &arguments.args {
+                if let syn::GenericArgument::AssocType(ref output) = *argument {
+                    if output.ident == "Output" {
+                        return Some(&output.ty);
+                    }
+                }
+            }
+        }
+    }
+    None
+}
+
+// How a parameter is passed to the Expectations.
+enum Param<'a> {
+    Value(&'a Type),
+    // `&'static T`, which is passed as it is
+    Static(&'a Type),
+    // `&T` and `&mut T`, which are passed as `Borrowed<T>` and `BorrowedMut<T>`
+    Borrowed(&'a syn::TypeReference),
+    BorrowedMut(&'a syn::TypeReference)
+}
+
+fn classify_param<'a>(ty: &'a Type) -> Param<'a> {
+    match *ty {
+        Type::Group(ref group) => classify_param(&group.elem),
+        Type::Reference(ref reference) => match reference.lifetime {
+            Some(ref lifetime) if lifetime.ident == "static" => Param::Static(ty),
+            _ if reference.mutability.is_some() => Param::BorrowedMut(reference),
+            _ => Param::Borrowed(reference)
+        },
+        _ => Param::Value(ty)
+    }
+}
+
+// Get the parameters of a method other than its receiver.
+fn typed_params(inputs: &Punctuated<FnArg, Token![,]>) -> Vec<&syn::PatType> {
+    inputs.iter().filter_map(|arg| match *arg {
+        FnArg::Typed(ref arg) => Some(arg),
+        FnArg::Receiver(_) => None
+    }).collect()
+}
+
+fn param_ident(param: &syn::PatType) -> &Ident {
+    match *param.pat {
+        syn::Pat::Ident(ref pat) => &pat.ident,
+        _ => unreachable!("Parameters are named by gather_method()")
+    }
+}
+
+// A single value is passed as it is, and several as a tuple.
+fn tuplefy(values: Vec<TokenStream2>) -> TokenStream2 {
+    if values.len() == 1 {
+        values.into_iter().next().unwrap()
+    } else {
+        quote!((#(#values),*))
+    }
+}
+
+fn generate_input_tuple(inputs: &Punctuated<FnArg, Token![,]>) -> TokenStream2 {
+    tuplefy(typed_params(inputs).into_iter().map(|param| match classify_param(&param.ty) {
+        Param::Value(ty) | Param::Static(ty) => quote!(#ty),
+        Param::Borrowed(reference) => {
+            let elem = &reference.elem;
+            quote!(::simulacrum::Borrowed<#elem>)
+        },
+        Param::BorrowedMut(reference) => {
+            let elem = &reference.elem;
+            quote!(::simulacrum::BorrowedMut<#elem>)
+        }
+    }).collect())
+}
+
+// Methods that aren't generic get a typed key from `generate_method_keys()`.
+fn generate_key(method: &Method) -> TokenStream2 {
+    let key = &method.key;
+    if method.sig.generics.params.is_empty() {
+        quote!(::simulacrum::MockKey::<Self, { ::simulacrum::__private::method_id(#key) }>::new())
+    } else {
+        quote!(::simulacrum::MethodKey::generic(#key))
+    }
+}
+
+// Implement `MockMethod` for each method that isn't generic, so that its key
+// only fits its types.
+fn generate_method_keys<'a, I: Iterator<Item = &'a Method>>(name: &Ident, generics: &syn::Generics, methods: I, args: &Args) -> TokenStream2 {
+    let cfg = args.cfg();
+    let lifetimes: Vec<&syn::Lifetime> = generics.lifetimes().map(|param| &param.lifetime).collect();
+    let type_params: Vec<&Ident> = generics.type_params().map(|param| &param.ident).collect();
+    let mut result = TokenStream2::new();
+    for method in methods.filter(|method| method.sig.generics.params.is_empty()) {
+        let inputs = &method.inputs;
+        let output = &method.output;
+        let key = &method.key;
+        result.extend(quote! {
+            #cfg
+            impl<#(#lifetimes,)* #(#type_params: 'static),*> ::simulacrum::__private::MockMethod<#inputs, #output, { ::simulacrum::__private::method_id(#key) }> for #name<#(#lifetimes,)* #(#type_params),*> {
+                const NAME: &'static str = #key;
+
+                fn params_formatter() -> Option<fn(&#inputs) -> String> {
+                    use ::simulacrum::__private::{FormatDebug as _, FormatUnknown as _};
+                    (&::simulacrum::__private::DebugParams::<#inputs>::new()).formatter()
+                }
+            }
+        });
     }
     result
 }
 
-// fn generate_expects(methods: &Vec<Method>) -> quote::Tokens {
-//     let mut result = quote::Tokens::new();
-//     for method in methods {
-//         let ident = &method.ident;
-//         let ident_tokens = quote!{ #ident };
-//         let ident_str = ident_tokens.as_str();
-//         let name = expectify_method_name(ident);
-//         let otype = generate_output_type(&method.sig.decl.output);
-//         let ituple = generate_input_tuple(&method.sig.decl.inputs);
-//         let expect_method = quote! {
-//             pub fn #name(&mut self) -> Method<#ituple, #otype> {
-//                 self.e.expect::<#ituple, #otype>(#ident_str)
-//             }
-//         };
-//         result.append(expect_method);
-//     }
-//     result
-// }
-
-fn expectify_method_name(ident: &syn::Ident) -> syn::Ident {
-    syn::Ident::new(format!("expect_{}", ident))
-}
-
-// fn generate_output_type(output: &syn::FunctionRetTy) -> quote::Tokens {
-//     match output {
-//         &syn::FunctionRetTy::Default => quote! { () },
-//         &syn::FunctionRetTy::Ty(ref ty) => quote! { #ty }
-//     }
-// }
-
-// fn generate_input_tuple(input: &Vec<syn::FnArg>) -> quote::Tokens {
-//     let types = gather_captured_arg_types(input);
-
-//     let mut result = quote::Tokens::new();
-//     let num_types = types.len();
-//     match num_types {
-//         1 => {
-//             let first = types.first().unwrap();
-//             first.to_tokens(&mut result);
-//         },
-//         _ => {
-//             result.append("(");
-//             let mut num_added = 0;
-//             for ty in types {
-//                 ty.to_tokens(&mut result);
-//                 num_added += 1;
-
-//                 if num_added < num_types {
-//                     result.append(",");
-//                 }
-//             }
-//             result.append(")");
-//         }
-//     }
-//     result
-// }
-
-// fn gather_captured_arg_types(input: &Vec<syn::FnArg>) -> Vec<syn::Ty> {
-//     let mut result = Vec::new();
-//     for arg in input {
-//         match arg {
-//             &syn::FnArg::Captured(ref _pattern, ref ty) => {
-//                 result.push(ty.clone());
-//             },
-//             _ => { }
-//         }
-//     }
-//     result
-// }
-
-fn gather_original_methods(methods: &Vec<Method>) -> Vec<quote::Tokens> {
+fn generate_expects(methods: &[&Method], vis: &TokenStream2) -> TokenStream2 {
+    let mut result = TokenStream2::new();
+    for method in methods {
+        let name = &method.expect_name;
+        let generics = &method.sig.generics;
+        let where_clause = &method.sig.generics.where_clause;
+        let inputs = &method.inputs;
+        let output = &method.output;
+        let key = generate_key(method);
+        result.extend(quote! {
+            #vis fn #name #generics (&mut self) -> ::simulacrum::Method<#inputs, #output> #where_clause {
+                self.e.expect::<#inputs, #output>(#key)
+            }
+        });
+    }
+    result
+}
+
+fn generate_stubs(methods: &[Method], vis: TokenStream2) -> Vec<TokenStream2> {
     let mut result = Vec::new();
     for method in methods {
-        let to_output = &method.original_item;
-        let tokens = quote! {
-            #to_output
+        let inputs = &method.inputs;
+        let output = &method.output;
+        let key = generate_key(method);
+
+        // Borrow & and &mut params from the scope of the call
+        let params = typed_params(&method.sig.inputs);
+        let borrows = params.iter().any(|param| {
+            matches!(classify_param(&param.ty), Param::Borrowed(_) | Param::BorrowedMut(_))
+        });
+        let values = tuplefy(params.iter().map(|param| {
+            let ident = param_ident(param);
+            match classify_param(&param.ty) {
+                Param::Borrowed(_) => quote!(__scope.borrow(#ident)),
+                Param::BorrowedMut(_) => quote!(__scope.borrow_mut(#ident)),
+                _ => quote!(#ident)
+            }
+        }).collect());
+
+        // Functions without `self` use the expectations installed for the mock
+        let expectations = if method.sig.receiver().is_some() {
+            quote!(self.e)
+        } else {
+            quote!(__e)
         };
+        let mut call = match method.returns {
+            Returns::Value if output.to_string() == "()" => quote! {
+                #expectations.was_called_responding::<#inputs, ()>(#key, #values)
+            },
+            Returns::Value => quote! {
+                #expectations.was_called_returning::<#inputs, #output>(#key, #values)
+            },
+            _ => quote! {
+                #expectations.was_called_async::<#inputs, #output>(#key, #values)
+            }
+        };
+        if borrows {
+            call = quote!(::simulacrum::Scope::with(|__scope| #call));
+        }
+        if method.sig.receiver().is_none() {
+            call = quote!(::simulacrum::Expectations::with_static::<Self, _, _>(|__e| #call));
+        }
+        if method.returns == Returns::BoxedFuture {
+            call = quote!(::std::boxed::Box::pin(#call));
+        }
+
+        // `async fn` is mocked with a function returning a future
+        let mut sig = method.sig.clone();
+        if method.returns == Returns::Async {
+            sig.asyncness = None;
+            sig.output = parse_quote!(-> impl ::std::future::Future<Output = #output>);
+        }
 
-        // Push the tokens onto our result Vec
-        result.push(tokens);
+        result.push(quote! {
+            #vis #sig {
+                #call
+            }
+        });
     }
     result
 }
 
-// fn generate_stubs(methods: &Vec<Method>) -> quote::Tokens {
-//     let mut result = quote::Tokens::new();
-//     for method in methods {
-//         let ident = &method.ident;
-//         let ident_tokens = quote!{ #ident };
-//         let ident_str = ident_tokens.as_str();
-//         let otype = generate_output_type(&method.sig.decl.output);
-//         let ituple = generate_input_tuple(&method.sig.decl.inputs);
-//         let itypes = generate_input_types(&method.sig.decl.inputs);
-//         // TODO: generate method sig tokens 
-//         let mut method_sig_tokens = quote::Tokens::new();
-//         // TODO: generate method sig tokens 
-//         let mut body_tokens = quote::Tokens::new();
-//         let stub_method = quote! {
-//             #method_sig_tokens {
-//                 #body_tokens
-//             }
-//         };
-//         result.append(stub_method);
-//     }
-//     result
-// }
-
-// fn generate_input_types(input: &Vec<syn::FnArg>) -> quote::Tokens {
-//     let mut result = quote::Tokens::new();
-//     // for arg in input {
-//     //     match arg {
-//     //         &syn::FnArg::Captured(ref _pattern, ref ty) => {
-//     //             result.push(ty.clone());
-//     //         },
-//     //         _ => { }
-//     //     }
-//     // }
-//     result
-// }
+// Forward calls to `__inner`. Only methods taking `&self` or `&mut self` are
+// forwarded, since `__inner` is shared and can't be consumed. Generic methods
+// and futures can't be forwarded either.
+fn generate_delegate(method: &Method, trait_path: &syn::Path) -> Option<TokenStream2> {
+    let receiver = match method.sig.receiver() {
+        Some(receiver) if receiver.reference.is_some() && receiver.colon_token.is_none() => receiver,
+        _ => return None
+    };
+    if !method.sig.generics.params.is_empty() || method.returns != Returns::Value {
+        return None;
+    }
+
+    let params = typed_params(&method.sig.inputs);
+    let mut patterns = Vec::new();
+    let mut args = Vec::new();
+    let mut borrows = Vec::new();
+    for param in &params {
+        let ident = param_ident(param);
+        match classify_param(&param.ty) {
+            // References with other named lifetimes might be kept beyond the
+            // call, so they can't be passed on from a Borrowed
+            Param::Borrowed(reference) | Param::BorrowedMut(reference) if reference.lifetime.is_some() => {
+                return None;
+            },
+            Param::Borrowed(_) => {
+                patterns.push(quote!(#ident));
+                args.push(quote!(#ident));
+                borrows.push((ident.clone(), quote!(with)));
+            },
+            Param::BorrowedMut(_) => {
+                patterns.push(quote!(mut #ident));
+                args.push(quote!(#ident));
+                borrows.push((ident.clone(), quote!(with_mut)));
+            },
+            _ => {
+                patterns.push(quote!(#ident));
+                args.push(quote!(#ident));
+            }
+        }
+    }
+
+    let method_ident = &method.sig.ident;
+    let inputs = &method.inputs;
+    let output = &method.output;
+    let key = generate_key(method);
+    let inner = if receiver.mutability.is_some() {
+        quote!(&mut *__inner.lock().unwrap())
+    } else {
+        quote!(&*__inner.lock().unwrap())
+    };
+    let unsafety = &method.sig.unsafety;
+    let pattern = tuplefy(patterns);
+
+    // Borrowed params are only reachable inside `with()` and `with_mut()`, so
+    // the call is nested inside them, with each param shadowed by its reference
+    let mut call = quote!(<__Inner as #trait_path>::#method_ident(#inner, #(#args),*));
+    for (ident, with) in borrows.into_iter().rev() {
+        call = quote!(#ident.#with(|#ident| #call));
+    }
+
+    Some(quote! {
+        {
+            let __inner = __inner.clone();
+            self.e.delegate::<#inputs, #output, _>(#key, move |#pattern| #unsafety {
+                #call
+            });
+        }
+    })
+}
+
+// The trait's path, with its associated types bound to what the mock uses.
+fn bind_associated_types(section: &Section) -> syn::Path {
+    let mut path = section.path.clone();
+    let bindings: Vec<&syn::ImplItemType> = section.items.iter().filter_map(|item| match *item {
+        syn::ImplItem::Type(ref binding) => Some(binding),
+        _ => None
+    }).collect();
+    if bindings.is_empty() {
+        return path;
+    }
+
+    let last = path.segments.last_mut().unwrap();
+    let mut arguments = match last.arguments {
+        syn::PathArguments::AngleBracketed(ref arguments) => arguments.args.clone(),
+        _ => Punctuated::new()
+    };
+    for binding in bindings {
+        let ident = &binding.ident;
+        let ty = &binding.ty;
+        arguments.push(parse_quote!(#ident = #ty));
+    }
+    last.arguments = syn::PathArguments::AngleBracketed(parse_quote!(<#arguments>));
+    path
+}
+
+// Generate the Mock struct, with `extra` added to its inherent methods.
+fn generate_struct(name: &Ident, generics: &syn::Generics, extra: TokenStream2, args: &Args) -> TokenStream2 {
+    let name_str = name.to_string();
+    let vis = args.vis();
+    let cfg = args.cfg();
+    let derives = &args.derives;
+    let derive = if derives.is_empty() {
+        quote!()
+    } else {
+        quote!(#[derive(#(#derives),*)])
+    };
+    let lifetimes: Vec<&syn::Lifetime> = generics.lifetimes().map(|param| &param.lifetime).collect();
+    let type_params: Vec<&Ident> = generics.type_params().map(|param| &param.ident).collect();
+
+    quote! {
+        #cfg
+        #derive
+        #vis struct #name<#(#lifetimes,)* #(#type_params),*> {
+            e: ::simulacrum::Expectations,
+            _params: ::std::marker::PhantomData<fn() -> (#(&#lifetimes (),)* #(#type_params,)*)>
+        }
+
+        #cfg
+        #[allow(dead_code)]
+        impl<#(#lifetimes,)* #(#type_params: 'static),*> #name<#(#lifetimes,)* #(#type_params),*> {
+            #vis fn new() -> Self {
+                Self {
+                    e: ::simulacrum::Expectations::with_name(#name_str),
+                    _params: ::std::marker::PhantomData
+                }
+            }
+
+            /// Same as `new()`, but the mock's expectations are also used by
+            /// the type's functions without `self` on this thread, until it's
+            /// dropped.
+            #vis fn with_statics() -> Self where Self: 'static {
+                Self {
+                    e: ::simulacrum::Expectations::with_name(#name_str).install_static::<Self>(),
+                    _params: ::std::marker::PhantomData
+                }
+            }
+
+            #vis fn then(&mut self) -> &mut Self {
+                self.e.then();
+                self
+            }
+
+            #vis fn strict(&mut self) -> &mut Self {
+                self.e.strict();
+                self
+            }
+
+            #vis fn verify_all(&self) -> Result<(), ::simulacrum::VerificationReport> {
+                self.e.verify_all()
+            }
+
+            #vis fn checkpoint(&mut self) -> Result<(), ::simulacrum::VerificationReport> {
+                self.e.checkpoint()
+            }
+
+            #vis fn record_calls(&mut self) -> &mut Self {
+                self.e.record_calls();
+                self
+            }
+
+            #vis fn record_params<I>(&mut self, name: &str) -> &mut Self where
+                I: Clone + ::simulacrum::MaybeSend + 'static
+            {
+                self.e.record_params::<I>(name);
+                self
+            }
+
+            #vis fn call_log(&self) -> Vec<::simulacrum::CallRecord> {
+                self.e.call_log()
+            }
+
+            #vis fn calls<I>(&self, name: &str) -> Vec<I> where
+                I: Clone + 'static
+            {
+                self.e.calls::<I>(name)
+            }
+
+            #extra
+        }
+
+        #cfg
+        impl<#(#lifetimes,)* #(#type_params: 'static),*> Default for #name<#(#lifetimes,)* #(#type_params),*> {
+            fn default() -> Self {
+                Self::new()
+            }
+        }
+    }
+}
+
+// Check for `#[simulacrum(default)]`, which keeps a method's default body.
+fn is_default_marked(attrs: &[syn::Attribute]) -> Result<bool> {
+    for attr in attrs {
+        if attr.path().is_ident("simulacrum") {
+            let option: Ident = attr.parse_args()?;
+            if option != "default" {
+                return Err(Error::new_spanned(&option, format!(
+                    "Unknown option `{}`. Methods can only be marked with `#[simulacrum(default)]`.", option
+                )));
+            }
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
+
+// Remove `#[simulacrum(default)]` from the trait's methods, since it only
+// means something to this macro.
+fn remove_default_markers(item: &mut syn::ItemTrait) {
+    for trait_item in &mut item.items {
+        if let syn::TraitItem::Fn(ref mut method) = *trait_item {
+            method.attrs.retain(|attr| !attr.path().is_ident("simulacrum"));
+        }
+    }
+}
+
+fn without_default_markers(input: TokenStream2) -> TokenStream2 {
+    match syn::parse2::<syn::ItemTrait>(input.clone()) {
+        Ok(mut item) => {
+            remove_default_markers(&mut item);
+            quote!(#item)
+        },
+        Err(_) => input
+    }
+}
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
-    // #[test]
-    // // Test for fn blah()
-    // fn test_generate_input_tuple_none() {
-    //     let input = Vec::new();
-
-    //     let expected = quote! { () };
-
-    //     let result = generate_input_tuple(&input);
-
-    //     assert_eq!(expected, result);
-    // }
-
-    // #[test]
-    // // Test for fn blah(arg: i32)
-    // fn test_generate_input_tuple_one_captured() {
-    //     let mut input = Vec::new();
-    //     // arg: i32
-    //     let binding_mode = syn::BindingMode::ByValue(syn::Mutability::Immutable);
-    //     let ident = syn::parse_ident("arg").unwrap();
-    //     let pattern = syn::Pat::Ident(binding_mode, ident, None);
-    //     let ty = syn::parse_type("i32").unwrap();
-    //     let arg = syn::FnArg::Captured(pattern, ty);
-    //     input.push(arg);
-
-    //     let expected = quote! { i32 };
-
-    //     let result = generate_input_tuple(&input);
-
-    //     assert_eq!(expected, result);
-    // }
-
-    // #[test]
-    // // Test for fn blah(&self)
-    // fn test_generate_input_tuple_self_ref() {
-    //     let mut input = Vec::new();
-    //     // &self
-    //     let arg = syn::FnArg::SelfRef(None, syn::Mutability::Immutable);
-    //     input.push(arg);
-
-    //     let expected = quote! { () };
-
-    //     let result = generate_input_tuple(&input);
-
-    //     assert_eq!(expected, result);
-    // }
-
-    // #[test]
-    // // Test for fn blah(&self, arg: i32)
-    // fn test_generate_input_tuple_self_ref_one_captured() {
-    //     let mut input = Vec::new();
-    //     // &self
-    //     let arg = syn::FnArg::SelfRef(None, syn::Mutability::Immutable);
-    //     input.push(arg);
-    //     // arg: i32
-    //     let binding_mode = syn::BindingMode::ByValue(syn::Mutability::Immutable);
-    //     let ident = syn::parse_ident("arg").unwrap();
-    //     let pattern = syn::Pat::Ident(binding_mode, ident, None);
-    //     let ty = syn::parse_type("i32").unwrap();
-    //     let arg = syn::FnArg::Captured(pattern, ty);
-    //     input.push(arg);
-
-    //     let expected = quote! { i32 };
-
-    //     let result = generate_input_tuple(&input);
-
-    //     assert_eq!(expected, result);
-    // }
-
-    // #[test]
-    // // Test for fn blah(&self, arg1: i32, arg2: bool)
-    // fn test_generate_input_tuple_self_ref_two_captured() {
-    //     let mut input = Vec::new();
-    //     // &self
-    //     let arg = syn::FnArg::SelfRef(None, syn::Mutability::Immutable);
-    //     input.push(arg);
-    //     // arg1: i32
-    //     let binding_mode = syn::BindingMode::ByValue(syn::Mutability::Immutable);
-    //     let ident = syn::parse_ident("arg1").unwrap();
-    //     let pattern = syn::Pat::Ident(binding_mode, ident, None);
-    //     let ty = syn::parse_type("i32").unwrap();
-    //     let arg = syn::FnArg::Captured(pattern, ty);
-    //     input.push(arg);
-    //     // arg2: bool
-    //     let binding_mode = syn::BindingMode::ByValue(syn::Mutability::Immutable);
-    //     let ident = syn::parse_ident("arg2").unwrap();
-    //     let pattern = syn::Pat::Ident(binding_mode, ident, None);
-    //     let ty = syn::parse_type("bool").unwrap();
-    //     let arg = syn::FnArg::Captured(pattern, ty);
-    //     input.push(arg);
-
-    //     let expected = quote! { ( i32, bool ) };
-
-    //     let result = generate_input_tuple(&input);
-
-    //     assert_eq!(expected, result);
-    // }
+    fn error_message(args: TokenStream2, input: TokenStream2) -> String {
+        match simulacrum_internal(args, input) {
+            Ok(_) => panic!("Expected an error."),
+            Err(err) => err.to_string()
+        }
+    }
 
     #[test]
-    fn it_works() {
+    // Test for fn blah()
+    fn test_generate_input_tuple_none() {
+        let sig: syn::Signature = parse_quote!(fn blah());
+
+        let expected = quote! { () };
+
+        let result = generate_input_tuple(&sig.inputs);
+
+        assert_eq!(expected.to_string(), result.to_string());
+    }
+
+    #[test]
+    // Test for fn blah(arg: i32)
+    fn test_generate_input_tuple_one_captured() {
+        let sig: syn::Signature = parse_quote!(fn blah(arg: i32));
+
+        let expected = quote! { i32 };
+
+        let result = generate_input_tuple(&sig.inputs);
+
+        assert_eq!(expected.to_string(), result.to_string());
+    }
+
+    #[test]
+    // Test for fn blah(&self)
+    fn test_generate_input_tuple_self_ref() {
+        let sig: syn::Signature = parse_quote!(fn blah(&self));
+
+        let expected = quote! { () };
+
+        let result = generate_input_tuple(&sig.inputs);
+
+        assert_eq!(expected.to_string(), result.to_string());
+    }
+
+    #[test]
+    // Test for fn blah(&self, arg: i32)
+    fn test_generate_input_tuple_self_ref_one_captured() {
+        let sig: syn::Signature = parse_quote!(fn blah(&self, arg: i32));
+
+        let expected = quote! { i32 };
+
+        let result = generate_input_tuple(&sig.inputs);
+
+        assert_eq!(expected.to_string(), result.to_string());
+    }
+
+    #[test]
+    // Test for fn blah(&self, arg1: i32, arg2: bool)
+    fn test_generate_input_tuple_self_ref_two_captured() {
+        let sig: syn::Signature = parse_quote!(fn blah(&self, arg1: i32, arg2: bool));
+
+        let expected = quote! { ( i32, bool ) };
+
+        let result = generate_input_tuple(&sig.inputs);
+
+        assert_eq!(expected.to_string(), result.to_string());
+    }
+
+    #[test]
+    // Test for fn blah(self: Box<Self>, name: &'static str, key: &'a str, buf: &mut [u8])
+    fn test_generate_input_tuple_references() {
+        let sig: syn::Signature = parse_quote!(fn blah(self: Box<Self>, name: &'static str, key: &'a str, buf: &mut [u8]));
+
+        let expected = quote! { ( &'static str, ::simulacrum::Borrowed<str>, ::simulacrum::BorrowedMut<[u8]> ) };
+
+        let result = generate_input_tuple(&sig.inputs);
+
+        assert_eq!(expected.to_string(), result.to_string());
+    }
+
+    #[test]
+    fn test_generate_output_type() {
+        let cases: Vec<(syn::Signature, TokenStream2)> = vec![
+            (parse_quote!(fn blah(&self)), quote! { () }),
+            (parse_quote!(fn blah(&self) -> u32), quote! { u32 }),
+            (parse_quote!(async fn blah(&self) -> u32), quote! { u32 }),
+            (parse_quote!(fn blah(&self) -> impl Future<Output = u32> + Send), quote! { u32 }),
+            (parse_quote!(fn blah(&self) -> Pin<Box<dyn Future<Output = u32> + Send>>), quote! { u32 }),
+            (parse_quote!(fn blah(&self) -> &'static str), quote! { &'static str }),
+            (parse_quote!(fn blah(&self) -> Box<dyn Fn(&str) -> usize>), quote! { Box<dyn Fn(&str) -> usize> })
+        ];
+
+        for (sig, expected) in cases {
+            let (_, result) = generate_output_type(&sig).unwrap();
+            assert_eq!(expected.to_string(), result.to_string());
+        }
+    }
+
+    #[test]
+    fn test_qualify_self() {
+        let path: syn::Path = parse_quote!(Storage<T>);
+        let mut sig: syn::Signature = parse_quote!(fn load(&self, key: Self::Key) -> Result<Vec<Self>, Self::Error>);
+
+        QualifySelf(&path).visit_signature_mut(&mut sig);
+
+        let expected: syn::Signature = parse_quote!(fn load(&self, key: <Self as Storage<T>>::Key) -> Result<Vec<Self>, <Self as Storage<T>>::Error>);
+        assert_eq!(quote!(#expected).to_string(), quote!(#sig).to_string());
+    }
+
+    #[test]
+    fn test_params_are_named() {
+        let sig: syn::Signature = parse_quote!(fn blah(&self, mut count: u32, (a, b): (u8, u8), _: bool));
+
+        let method = gather_method(&sig, "blah".to_owned(), None).unwrap();
+
+        let expected: syn::Signature = parse_quote!(fn blah(&self, count: u32, __arg2: (u8, u8), __arg3: bool));
+        let result = &method.sig;
+        assert_eq!(quote!(#expected).to_string(), quote!(#result).to_string());
+    }
+
+    #[test]
+    fn test_bind_associated_types() {
+        let section = Section {
+            path: parse_quote!(Storage<T>),
+            items: vec![parse_quote!(type Error = io::Error;), parse_quote!(const NAME: &'static str = "mock";)],
+            methods: vec![]
+        };
+
+        let expected: syn::Path = parse_quote!(Storage<T, Error = io::Error>);
+        let result = bind_associated_types(&section);
+        assert_eq!(quote!(#expected).to_string(), quote!(#result).to_string());
+    }
+
+    #[test]
+    fn test_supertraits_are_namespaced() {
+        let input = quote! {
+            trait Service: Named + Send {
+                fn call(&self, request: u32) -> u32;
+            }
+        };
+
+        let result = simulacrum_internal(quote!(supertraits(Named { fn name(&self) -> String; })), input).unwrap().to_string();
+
+        assert!(result.contains("\"Service::call\""));
+        assert!(result.contains("\"Named::name\""));
+        assert!(result.contains("impl Named for ServiceMock"));
+    }
+
+    #[test]
+    fn test_default_markers_are_removed() {
+        let input = quote! {
+            trait Greeter {
+                fn name(&self) -> String;
+
+                #[simulacrum(default)]
+                fn greet(&self) -> String {
+                    format!("Hello, {}!", self.name())
+                }
+            }
+        };
+
+        let result = simulacrum_internal(quote!(), input).unwrap().to_string();
+
+        assert!(!result.contains("simulacrum (default)"));
+        assert!(result.contains("expect_name"));
+        assert!(!result.contains("expect_greet"));
+    }
+
+    #[test]
+    fn test_static_functions() {
+        let input = quote! {
+            pub trait Connection {
+                fn connect(host: &str, port: u16) -> Self;
+                fn default_port() -> u16;
+            }
+        };
+
+        let result = simulacrum_internal(quote!(), input).unwrap().to_string();
+
+        assert!(result.contains("fn with_statics"));
+        assert!(result.contains(&quote!(::simulacrum::Expectations::with_static::<Self, _, _>).to_string()));
+        assert!(result.contains(&quote!(__scope.borrow(host)).to_string()));
+    }
+
+    #[test]
+    fn test_receivers() {
         let input = quote! {
-            pub trait CoolTrait {
-                /// Shared self - Also, Doc comments
-                fn foo(&self);
+            pub trait Request {
+                fn send(self) -> u16;
+                fn send_boxed(self: Box<Self>) -> u16;
+                fn poll_ready(self: Pin<&mut Self>) -> bool;
+                fn get(&self) -> u16;
+            }
+        };
 
-                // Mutable self
-                fn bar(&mut self);
+        let result = simulacrum_internal(quote!(), input).unwrap().to_string();
 
-                // One parameter and returning a value
-                fn goop(&mut self, flag: bool) -> u32;
+        assert!(result.contains(&quote!(fn send_boxed(self: Box<Self>) -> u16).to_string()));
+        assert!(result.contains(&quote!(fn poll_ready(self: Pin<&mut Self>) -> bool).to_string()));
+        // Only methods borrowing `self` are forwarded by delegate_to()
+        assert!(result.contains(&quote!(<__Inner as Request>::get).to_string()));
+        assert!(!result.contains(&quote!(<__Inner as Request>::send).to_string()));
+    }
 
-                // Multiple parameters
-                fn zing(&self, first: i32, second: &mut bool);
+    #[test]
+    fn test_method_keys() {
+        let input = quote! {
+            trait Store {
+                fn get(&self, key: u32) -> String;
 
-                // Note: It doesn't work with references yet!
-                // fn boop(&self, name: &'static str)
+                fn put<T: 'static>(&self, value: T);
             }
         };
 
-        let expected = quote! {
-            pub trait CoolTrait {
-                /// Shared self - Also, Doc comments
-                fn foo(&self);
-                fn bar(&mut self);
-                fn goop(&mut self, flag: bool) -> u32;
-                fn zing(&self, first: i32, second: &mut bool);
+        let result = simulacrum_internal(quote!(), input).unwrap().to_string();
+
+        // Only methods that aren't generic get a typed key
+        let key = quote!(::simulacrum::MockKey::<Self, { ::simulacrum::__private::method_id("get") }>::new());
+        assert!(result.contains(&quote!(::simulacrum::__private::MockMethod<u32, String, { ::simulacrum::__private::method_id("get") }> for StoreMock).to_string()));
+        assert!(result.contains(&quote!(self.e.expect::<u32, String>(#key)).to_string()));
+        assert!(result.contains(&quote!(self.e.was_called_returning::<u32, String>(#key, key)).to_string()));
+        assert!(!result.contains("method_id (\"put\")"));
+    }
+
+    #[test]
+    fn test_borrowed_for_lifetime() {
+        let input = quote! {
+            trait Parser<'a> {
+                fn parse(&self, input: &'a str) -> usize;
+
+                fn peek(&self, input: &str) -> usize;
             }
+        };
+
+        let result = simulacrum_internal(quote!(), input).unwrap().to_string();
+
+        // Params borrowed for `'a` might outlive the call, so they aren't forwarded
+        assert!(!result.contains(&quote!(<__Inner as Parser<'a> >::parse).to_string()));
+        assert!(result.contains(&quote!(<__Inner as Parser<'a> >::peek).to_string()));
+        assert!(!result.contains("panic"));
+    }
+
+    #[test]
+    fn test_inherent_impl() {
+        let input = quote! {
+            impl Database {
+                const LIMIT: usize = 10;
+
+                pub fn new() -> Self {
+                    Database
+                }
 
-            create_mock! {
-                impl CoolTrait for CoolTraitMock (self) {
-                    expect_foo("foo"):
-                    fn foo(&self);
+                fn query(&self, sql: &str) -> Vec<String> {
+                    vec![sql.to_owned()]
+                }
+            }
+        };
 
-                    expect_bar("bar"):
-                    fn bar(&mut self);
+        let result = simulacrum_internal(quote!(), input).unwrap().to_string();
 
-                    expect_goop("goop"):
-                    fn goop(&mut self, flag: bool) -> u32;
+        assert!(result.contains("pub struct DatabaseMock"));
+        assert!(result.contains(&quote!(const LIMIT: usize = 10;).to_string()));
+        assert!(result.contains(&quote!(pub fn query(&self, sql: &str) -> Vec<String>).to_string()));
+        assert!(result.contains("expect_query"));
+        assert!(!result.contains("expect_new"));
+    }
 
-                    expect_zing("zing"):
-                    fn zing(&self, first: i32, second: &mut bool);
+    #[test]
+    fn test_inherent_new_with_params() {
+        let input = quote! {
+            impl Database {
+                pub fn new(url: &str) -> Self {
+                    Database
                 }
             }
         };
 
-        let result = simulacrum_internal(input.as_str());
-
-        assert_eq!(expected, result);
-    }
-
-    // #[test]
-    // #[ignore]
-    // fn it_works() {
-    //     let input = quote! {
-    //         pub trait CoolTrait {
-    //             // Shared self
-    //             fn foo(&self);
-
-    //             // Mutable self
-    //             fn bar(&mut self);
-
-    //             // One parameter and returning a value
-    //             fn goop(&mut self, flag: bool) -> u32;
-
-    //             // Multiple parameters
-    //             fn zing(&self, first: i32, second: bool);
-
-    //             // Note: It doesn't work with references yet!
-    //             // fn boop(&self, name: &'static str)
-    //         }
-    //     };
-
-    //     let expected = quote! {
-    //         pub trait CoolTrait {
-    //             fn foo(&self);
-    //             fn bar(&mut self);
-    //             fn goop(&mut self, flag: bool) -> u32;
-    //             fn zing(&self, first: i32, second: bool);
-    //         }
-
-    //         pub struct CoolTraitMock {
-    //             e: Expectations
-    //         }
-
-    //         impl CoolTraitMock {
-    //             pub fn new() -> Self {
-    //                 Self {
-    //                     e: Expectations::new()
-    //                 }
-    //             }
-
-    //             pub fn then(&mut self) -> &mut Self {
-    //                 self.e.then();
-    //                 self
-    //             }
-
-    //             pub fn expect_foo(&mut self) -> Method<(), ()> {
-    //                 self.e.expect::<(), ()>("foo")
-    //             }
-
-    //             pub fn expect_bar(&mut self) -> Method<(), ()> {
-    //                 self.e.expect::<(), ()>("bar")
-    //             }
-
-    //             pub fn expect_goop(&mut self) -> Method<bool, u32> {
-    //                 self.e.expect::<bool, u32>("goop")
-    //             }
-
-    //             pub fn expect_zing(&mut self) -> Method<(i32, bool), ()> {
-    //                 self.e.expect::<(i32, bool), ()>("zing")
-    //             }
-    //         }
-
-    //         impl CoolTrait for CoolTraitMock {
-    //             fn foo(&self) {
-    //                 self.e.was_called::<(), ()>("foo", ())
-    //             }
-
-    //             fn bar(&mut self) {
-    //                 self.e.was_called::<(), ()>("bar", ())
-    //             }
-
-    //             fn goop(&mut self, flag: bool) -> u32 {
-    //                 self.e.was_called_returning::<bool, u32>("goop", flag)
-    //             }
-
-    //             fn zing(&self, first: i32, second: bool) {
-    //                 self.e.was_called::<(i32, bool), ()>("zing", (first, second))
-    //             }
-    //         }
-    //     };
-
-    //     let result = simulacrum_internal(input.as_str());
-
-    //     assert_eq!(expected, result);
-    // }
+        let message = "`new` can't be mocked, since `DatabaseMock` has its own `new()` that takes no parameters and returns `Self`. \
+                       Move this one to another impl block, or give it another name.";
+        assert_eq!(error_message(quote!(), input), message);
+    }
+
+    #[test]
+    fn test_options() {
+        let input = quote! {
+            trait Store {
+                fn get(&self, key: u32) -> Option<i32>;
+            }
+        };
+
+        let result = simulacrum_internal(quote!(name = "FakeStore", vis = "pub(crate)", cfg_test, derive(Debug)), input).unwrap().to_string();
+
+        assert!(result.contains(&quote!(#[cfg(test)] #[derive(Debug)] pub(crate) struct FakeStore).to_string()));
+        assert!(result.contains(&quote!(#[cfg(test)] impl Store for FakeStore).to_string()));
+        assert!(result.contains(&quote!(pub(crate) fn expect_get(&mut self)).to_string()));
+        assert!(result.contains(&quote!(with_name("FakeStore")).to_string()));
+        assert!(!result.contains("StoreMock"));
+        assert!(!result.contains(&quote!(pub fn).to_string()));
+    }
+
+    #[test]
+    fn test_invalid_options() {
+        assert_eq!(
+            error_message(quote!(vis = "public"), quote!(trait Service { })),
+            "`vis` needs to be a visibility, like `vis = \"pub(crate)\"`."
+        );
+        assert_eq!(
+            error_message(quote!(name = "Fake Service"), quote!(trait Service { })),
+            "`name` needs to be an identifier, like `name = \"FakeStore\"`."
+        );
+        assert_eq!(
+            error_message(quote!(derive(Debug, Default)), quote!(trait Service { })),
+            "Only `Debug` can be derived for mocks."
+        );
+    }
+
+    #[test]
+    fn test_borrowed_return_types() {
+        let message = "Return types borrowing for lifetimes other than `'static` can't be mocked.";
+        assert_eq!(error_message(quote!(), quote!(trait Named { fn name(&self) -> &str; })), message);
+        assert_eq!(error_message(quote!(), quote!(trait Named<'a> { fn name(&self) -> Option<&'a str>; })), message);
+        assert_eq!(error_message(quote!(), quote!(trait Named { fn name(&self) -> Cow<'_, str>; })), message);
+        assert_eq!(error_message(quote!(), quote!(trait Named { async fn name(&self) -> &str; })), message);
+    }
+
+    #[test]
+    fn test_associated_type_without_binding() {
+        let input = quote! {
+            pub trait Storage {
+                type Error;
+
+                fn load(&self, key: u32) -> Result<Vec<u8>, Self::Error>;
+            }
+        };
+
+        assert_eq!(
+            error_message(quote!(), input),
+            "Associated type `Error` needs a binding, like `#[simulacrum(type Error = ...;)]`."
+        );
+    }
+
+    #[test]
+    fn test_unused_binding() {
+        let input = quote! {
+            pub trait Storage {
+                fn load(&self, key: u32) -> Vec<u8>;
+            }
+        };
+
+        assert_eq!(
+            error_message(quote!(type Error = io::Error;), input),
+            "`Error` isn't an associated type or constant of `Storage`."
+        );
+    }
+
+    #[test]
+    fn test_supertrait_without_definition() {
+        let input = quote! {
+            pub trait Service: Clone {
+                fn call(&self, request: u32) -> u32;
+            }
+        };
+
+        assert_eq!(
+            error_message(quote!(), input),
+            "Supertrait `Clone` needs a definition, like `#[simulacrum(supertraits(Clone { ... }))]`."
+        );
+    }
+
+    #[test]
+    fn test_default_method_without_body() {
+        let input = quote! {
+            pub trait Greeter {
+                #[simulacrum(default)]
+                fn name(&self) -> String;
+            }
+        };
+
+        assert_eq!(
+            error_message(quote!(), input),
+            "Method `name` is marked `#[simulacrum(default)]`, but has no default body."
+        );
+    }
+
+    #[test]
+    fn test_unsupported_items() {
+        assert_eq!(
+            error_message(quote!(), quote!(struct Database;)),
+            "#[simulacrum] can only mock traits and inherent impl blocks."
+        );
+        assert_eq!(
+            error_message(quote!(), quote!(impl Service for Database { })),
+            "#[simulacrum] can't mock trait impls. Put it on the trait instead."
+        );
+        assert_eq!(
+            error_message(quote!(), quote!(trait Service { fn call(&self, f: impl Fn()); })),
+            "`impl Trait` parameters can't be mocked. Use a generic parameter instead."
+        );
+        assert_eq!(
+            error_message(quote!(), quote!(impl Database { fn then(&self) { } })),
+            "`then` can't be mocked, since mock objects have a method with the same name."
+        );
+        assert_eq!(
+            error_message(quote!(label = "Foo"), quote!(trait Service { })),
+            "Unknown argument `label`."
+        );
+    }
 }
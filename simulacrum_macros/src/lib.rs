@@ -1,10 +1,29 @@
 extern crate simulacrum_mock;
 
-pub use simulacrum_mock::{Expectations, Method};
+pub use simulacrum_mock::{AsMethodKey, Borrowed, BorrowedMut, CallRecord, Expectations, MaybeSend, Method, MethodKey, MockKey, Scope, VerificationReport};
+pub use simulacrum_mock::{block_on, poll_once, MockFuture, Resolver};
+
+// Paths used by the macros, so that they resolve the same way in every crate.
+#[doc(hidden)]
+pub mod __private {
+    pub use simulacrum_mock::method::{method_id, DebugParams, FormatDebug, FormatUnknown, MockMethod};
+    pub use std::boxed::Box;
+    pub use std::future::Future;
+    pub use std::pin::Pin;
+}
 
 /// Use this macro to create an `.expect_METHOD_NAME()` method.
 #[macro_export]
 macro_rules! create_expect_method {
+    ($name:ident [$($generics:tt)*] ($key:expr) $inputs:ty => $output:ty) => {
+        #[allow(non_snake_case)]
+        pub fn $name<$($generics)*>(&mut self) -> $crate::Method<$inputs, $output> {
+            self.e.expect::<$inputs, $output>($crate::MethodKey::generic($key))
+        }
+    };
+    ($name:ident [$($generics:tt)*] ($key:expr) $inputs:ty) => {
+        create_expect_method!($name [$($generics)*] ($key) $inputs => ());
+    };
     ($name:ident($key:expr) $inputs:ty => $output:ty) => {
         #[allow(non_snake_case)]
         pub fn $name(&mut self) -> $crate::Method<$inputs, $output> {
@@ -19,21 +38,63 @@ macro_rules! create_expect_method {
     };
 }
 
-/// Use this macro to do a `self.e.was_called` `self.e.was_called_returning` with
+/// Use this macro to do a `self.e.was_called_responding` `self.e.was_called_returning` with
 /// a shorter interface.
 #[macro_export]
 macro_rules! was_called {
+    // Functions without `self` use the expectations installed for `Self`, or
+    // for another mock type, with `Expectations::install_static()`.
+    (Self, $($tail:tt)*) => {
+        was_called!(<Self>, $($tail)*)
+    };
+    (<$mock:ty>, $key:expr, $sig:tt -> async $output:ty) => {
+        #[allow(unused_parens)]
+        $crate::Scope::with(|scope| {
+            $crate::Expectations::with_static::<$mock, _, _>(|e| {
+                e.was_called_async::<simulacrum_tuplefy!(kind $sig -> ()), $output>($key, simulacrum_tuplefy!([borrow scope] $sig -> ()))
+            })
+        })
+    };
+    (<$mock:ty>, $key:expr, $sig:tt -> $output:ty) => {
+        #[allow(unused_parens)]
+        $crate::Scope::with(|scope| {
+            $crate::Expectations::with_static::<$mock, _, _>(|e| {
+                e.was_called_returning::<simulacrum_tuplefy!(kind $sig -> ()), $output>($key, simulacrum_tuplefy!([borrow scope] $sig -> ()))
+            })
+        })
+    };
+    (<$mock:ty>, $key:expr, $sig:tt) => {
+        #[allow(unused_parens)]
+        $crate::Scope::with(|scope| {
+            $crate::Expectations::with_static::<$mock, _, _>(|e| {
+                e.was_called_responding::<simulacrum_tuplefy!(kind $sig -> ()), ()>($key, simulacrum_tuplefy!([borrow scope] $sig -> ()))
+            })
+        })
+    };
+    (<$mock:ty>, $key:expr) => {
+        $crate::Expectations::with_static::<$mock, _, _>(|e| e.was_called_responding::<(), ()>($key, ()))
+    };
+    ($self_:ident, $key:expr, $sig:tt -> async $output:ty) => {
+        #[allow(unused_parens)]
+        $crate::Scope::with(|scope| {
+            $self_.e.was_called_async::<simulacrum_tuplefy!(kind $sig -> ()), $output>($key, simulacrum_tuplefy!([borrow scope] $sig -> ()))
+        })
+    };
     ($self_:ident, $key:expr, $sig:tt -> $output:ty) => {
         #[allow(unused_parens)]
-        $self_.e.was_called_returning::<simulacrum_tuplefy!(kind $sig -> ()), $output>($key, simulacrum_tuplefy!(name $sig -> ()))
+        $crate::Scope::with(|scope| {
+            $self_.e.was_called_returning::<simulacrum_tuplefy!(kind $sig -> ()), $output>($key, simulacrum_tuplefy!([borrow scope] $sig -> ()))
+        })
     };
     ($self_:ident, $key:expr, $sig:tt) => {
         #[allow(unused_parens)]
-        $self_.e.was_called::<simulacrum_tuplefy!(kind $sig -> ()), ()>($key, simulacrum_tuplefy!(name $sig -> ()))
+        $crate::Scope::with(|scope| {
+            $self_.e.was_called_responding::<simulacrum_tuplefy!(kind $sig -> ()), ()>($key, simulacrum_tuplefy!([borrow scope] $sig -> ()))
+        })
     };
     ($self_:ident, $key:expr) => {
         #[allow(unused_parens)]
-        $self_.e.was_called::<(), ()>($key, ())
+        $self_.e.was_called_responding::<(), ()>($key, ())
     };
 }
 
@@ -46,13 +107,28 @@ macro_rules! simulacrum_tuplefy {
     // See https://danielkeep.github.io/tlborm/book/blk-ast-coercion.html
     (@as_ty $token:ty) => { $token };
     (@as_expr $token:expr) => { $token };
+    (@as_pat $token:pat) => { $token };
+
+    // call - Call `target` with the parameters taken back out of the tuple.
+    (call ($($target:tt)*) ($($param:tt)*)) => {
+        simulacrum_tuplefy!(@inner [call ($($target)*)] ($($param)*) -> ())
+    };
+
+    // Nest the call inside `with()` or `with_mut()` for each Borrowed or
+    // BorrowedMut param, which shadows it with the reference.
+    (@call_with [] $($call:tt)*) => {
+        $($call)*
+    };
+    (@call_with [$name:ident $with:ident $($wrap:tt)*] $($call:tt)*) => {
+        $name.$with(|$name| simulacrum_tuplefy!(@call_with [$($wrap)*] $($call)*))
+    };
 
     // main - Strip off parentheses
     ($mode:tt ($($param:tt)*) -> ($($result:tt)*)) => {
         simulacrum_tuplefy!(@inner $mode ($($param)*) -> ())
     };
 
-    // simulacrum_tuplefy - For each param, get the type. Ignore &self and &mut self.
+    // simulacrum_tuplefy - For each param, get the type. Ignore the receiver.
 
     // If there are no params left, coerce the final result to a type with
     // parentheses around it.
@@ -62,6 +138,15 @@ macro_rules! simulacrum_tuplefy {
     (@inner name () -> ($($result:tt)*)) => {
         simulacrum_tuplefy!(@as_expr ( $($result)* ))
     };
+    (@inner [borrow $scope:ident] () -> ($($result:tt)*)) => {
+        simulacrum_tuplefy!(@as_expr ( $($result)* ))
+    };
+    (@inner pat () -> ($($result:tt)*)) => {
+        simulacrum_tuplefy!(@as_pat ( $($result)* ))
+    };
+    (@inner [call ($($target:tt)*) $($wrap:tt)*] () -> ($($result:tt)*)) => {
+        simulacrum_tuplefy!(@call_with [$($wrap)*] $($target)*( $($result)* ))
+    };
     
     // Ignore &self and &mut self.
     (@inner $mode:tt (& self) -> ($($result:tt)*)) => {
@@ -77,6 +162,32 @@ macro_rules! simulacrum_tuplefy {
         simulacrum_tuplefy!( @inner $mode ($($tail)*) -> ($($result)*) )
     };
 
+    // Ignore self taken by value, and typed receivers like `self: Box<Self>`.
+    (@inner $mode:tt (self) -> ($($result:tt)*)) => {
+        simulacrum_tuplefy!( @inner $mode () -> ($($result)*) )
+    };
+    (@inner $mode:tt (mut self) -> ($($result:tt)*)) => {
+        simulacrum_tuplefy!( @inner $mode () -> ($($result)*) )
+    };
+    (@inner $mode:tt (self, $($tail:tt)*) -> ($($result:tt)*)) => {
+        simulacrum_tuplefy!( @inner $mode ($($tail)*) -> ($($result)*) )
+    };
+    (@inner $mode:tt (mut self, $($tail:tt)*) -> ($($result:tt)*)) => {
+        simulacrum_tuplefy!( @inner $mode ($($tail)*) -> ($($result)*) )
+    };
+    (@inner $mode:tt (self: $receiver:ty) -> ($($result:tt)*)) => {
+        simulacrum_tuplefy!( @inner $mode () -> ($($result)*) )
+    };
+    (@inner $mode:tt (mut self: $receiver:ty) -> ($($result:tt)*)) => {
+        simulacrum_tuplefy!( @inner $mode () -> ($($result)*) )
+    };
+    (@inner $mode:tt (self: $receiver:ty, $($tail:tt)*) -> ($($result:tt)*)) => {
+        simulacrum_tuplefy!( @inner $mode ($($tail)*) -> ($($result)*) )
+    };
+    (@inner $mode:tt (mut self: $receiver:ty, $($tail:tt)*) -> ($($result:tt)*)) => {
+        simulacrum_tuplefy!( @inner $mode ($($tail)*) -> ($($result)*) )
+    };
+
     // Accept &'static params.
     (@inner kind ($name:ident: &'static $kind:ty) -> ($($result:tt)*)) => {
         simulacrum_tuplefy!( @inner kind () -> ($($result)* &'static $kind) )
@@ -85,18 +196,47 @@ macro_rules! simulacrum_tuplefy {
         simulacrum_tuplefy!( @inner kind ($($tail)*) -> ($($result)* &'static $kind,) )
     };
 
-    // Convert & and &mut params to *const and *mut.
-    (@inner kind ($name:ident: & $kind:ty) -> ($($result:tt)*)) => {
-        simulacrum_tuplefy!( @inner kind () -> ($($result)* *const $kind) )
+    // Pass &'static params to Expectations and calls as they are.
+    (@inner [borrow $scope:ident] ($name:ident: &'static $kind:ty) -> ($($result:tt)*)) => {
+        simulacrum_tuplefy!( @inner [borrow $scope] () -> ($($result)* $name) )
+    };
+    (@inner [borrow $scope:ident] ($name:ident: &'static $kind:ty, $($tail:tt)*) -> ($($result:tt)*)) => {
+        simulacrum_tuplefy!( @inner [borrow $scope] ($($tail)*) -> ($($result)* $name,) )
+    };
+    (@inner [call $($call:tt)*] ($name:ident: &'static $kind:ty) -> ($($result:tt)*)) => {
+        simulacrum_tuplefy!( @inner [call $($call)*] () -> ($($result)* $name) )
+    };
+    (@inner [call $($call:tt)*] ($name:ident: &'static $kind:ty, $($tail:tt)*) -> ($($result:tt)*)) => {
+        simulacrum_tuplefy!( @inner [call $($call)*] ($($tail)*) -> ($($result)* $name,) )
+    };
+
+    // Other named lifetimes don't matter to Expectations, so drop them and
+    // treat the params like any other reference.
+    (@inner $mode:tt ($name:ident: & $lifetime:lifetime mut $kind:ty) -> ($($result:tt)*)) => {
+        simulacrum_tuplefy!( @inner $mode ($name: & mut $kind) -> ($($result)*) )
+    };
+    (@inner $mode:tt ($name:ident: & $lifetime:lifetime $kind:ty) -> ($($result:tt)*)) => {
+        simulacrum_tuplefy!( @inner $mode ($name: & $kind) -> ($($result)*) )
+    };
+    (@inner $mode:tt ($name:ident: & $lifetime:lifetime mut $kind:ty, $($tail:tt)*) -> ($($result:tt)*)) => {
+        simulacrum_tuplefy!( @inner $mode ($name: & mut $kind, $($tail)*) -> ($($result)*) )
     };
+    (@inner $mode:tt ($name:ident: & $lifetime:lifetime $kind:ty, $($tail:tt)*) -> ($($result:tt)*)) => {
+        simulacrum_tuplefy!( @inner $mode ($name: & $kind, $($tail)*) -> ($($result)*) )
+    };
+
+    // Convert &mut and & params to BorrowedMut and Borrowed.
     (@inner kind ($name:ident: & mut $kind:ty) -> ($($result:tt)*)) => {
-        simulacrum_tuplefy!( @inner kind () -> ($($result)* *mut $kind) )
+        simulacrum_tuplefy!( @inner kind () -> ($($result)* $crate::BorrowedMut<$kind>) )
     };
-    (@inner kind ($name:ident: & $kind:ty, $($tail:tt)*) -> ($($result:tt)*)) => {
-        simulacrum_tuplefy!( @inner kind ($($tail)*) -> ($($result)* *const $kind,) )
+    (@inner kind ($name:ident: & $kind:ty) -> ($($result:tt)*)) => {
+        simulacrum_tuplefy!( @inner kind () -> ($($result)* $crate::Borrowed<$kind>) )
     };
     (@inner kind ($name:ident: & mut $kind:ty, $($tail:tt)*) -> ($($result:tt)*)) => {
-        simulacrum_tuplefy!( @inner kind ($($tail)*) -> ($($result)* *mut $kind,) )
+        simulacrum_tuplefy!( @inner kind ($($tail)*) -> ($($result)* $crate::BorrowedMut<$kind>,) )
+    };
+    (@inner kind ($name:ident: & $kind:ty, $($tail:tt)*) -> ($($result:tt)*)) => {
+        simulacrum_tuplefy!( @inner kind ($($tail)*) -> ($($result)* $crate::Borrowed<$kind>,) )
     };
 
     // Get the type of the parameter and move on.
@@ -107,44 +247,190 @@ macro_rules! simulacrum_tuplefy {
         simulacrum_tuplefy!( @inner kind () -> ($($result)* $kind) )
     };
 
+    // Borrow & and &mut params from the scope of the call, so that they can be
+    // passed to Expectations.
+    (@inner [borrow $scope:ident] ($name:ident: & mut $kind:ty) -> ($($result:tt)*)) => {
+        simulacrum_tuplefy!( @inner [borrow $scope] () -> ($($result)* $scope.borrow_mut($name)) )
+    };
+    (@inner [borrow $scope:ident] ($name:ident: & $kind:ty) -> ($($result:tt)*)) => {
+        simulacrum_tuplefy!( @inner [borrow $scope] () -> ($($result)* $scope.borrow($name)) )
+    };
+    (@inner [borrow $scope:ident] ($name:ident: & mut $kind:ty, $($tail:tt)*) -> ($($result:tt)*)) => {
+        simulacrum_tuplefy!( @inner [borrow $scope] ($($tail)*) -> ($($result)* $scope.borrow_mut($name),) )
+    };
+    (@inner [borrow $scope:ident] ($name:ident: & $kind:ty, $($tail:tt)*) -> ($($result:tt)*)) => {
+        simulacrum_tuplefy!( @inner [borrow $scope] ($($tail)*) -> ($($result)* $scope.borrow($name),) )
+    };
+
+    // Bind &mut params mutably, so that they can be borrowed mutably again.
+    (@inner pat ($name:ident: & mut $kind:ty) -> ($($result:tt)*)) => {
+        simulacrum_tuplefy!( @inner pat () -> ($($result)* mut $name) )
+    };
+    (@inner pat ($name:ident: & mut $kind:ty, $($tail:tt)*) -> ($($result:tt)*)) => {
+        simulacrum_tuplefy!( @inner pat ($($tail)*) -> ($($result)* mut $name,) )
+    };
+
+    // Convert Borrowed and BorrowedMut params back to & and &mut for the call.
+    (@inner [call $($call:tt)*] ($name:ident: & mut $kind:ty) -> ($($result:tt)*)) => {
+        simulacrum_tuplefy!( @inner [call $($call)* $name with_mut] () -> ($($result)* $name) )
+    };
+    (@inner [call $($call:tt)*] ($name:ident: & $kind:ty) -> ($($result:tt)*)) => {
+        simulacrum_tuplefy!( @inner [call $($call)* $name with] () -> ($($result)* $name) )
+    };
+    (@inner [call $($call:tt)*] ($name:ident: & mut $kind:ty, $($tail:tt)*) -> ($($result:tt)*)) => {
+        simulacrum_tuplefy!( @inner [call $($call)* $name with_mut] ($($tail)*) -> ($($result)* $name,) )
+    };
+    (@inner [call $($call:tt)*] ($name:ident: & $kind:ty, $($tail:tt)*) -> ($($result:tt)*)) => {
+        simulacrum_tuplefy!( @inner [call $($call)* $name with] ($($tail)*) -> ($($result)* $name,) )
+    };
+
     // Get the name of the parameter and move on.
-    (@inner name ($name:ident: $kind:ty, $($tail:tt)*) -> ($($result:tt)*)) => {
-        simulacrum_tuplefy!( @inner name ($($tail)*) -> ($($result)* $name,) )
+    (@inner $mode:tt ($name:ident: $kind:ty, $($tail:tt)*) -> ($($result:tt)*)) => {
+        simulacrum_tuplefy!( @inner $mode ($($tail)*) -> ($($result)* $name,) )
     };
-    (@inner name ($name:ident: $kind:ty) -> ($($result:tt)*)) => {
-        simulacrum_tuplefy!( @inner name () -> ($($result)* $name) )
+    (@inner $mode:tt ($name:ident: $kind:ty) -> ($($result:tt)*)) => {
+        simulacrum_tuplefy!( @inner $mode () -> ($($result)* $name) )
+    };
+}
+
+// Qualify `Self::Name` paths in a list of tokens as `<Self as Trait>::Name`, so
+// that associated types of a trait can be named outside of its impl. The
+// result is passed on as `$callback!($($prefix)* result $($suffix)*)`.
+// Uses a stack to descend into parentheses and brackets.
+#[macro_export]
+macro_rules! simulacrum_qualify_self {
+    (@munch ($($trait_path:tt)*) $callback:tt $stack:tt ($($result:tt)*) Self :: $name:ident $($tail:tt)*) => {
+        simulacrum_qualify_self!(@munch ($($trait_path)*) $callback $stack ($($result)* <Self as $($trait_path)*>::$name) $($tail)*)
+    };
+    (@munch $trait_path:tt $callback:tt [$($stack:tt)*] $result:tt ($($group:tt)*) $($tail:tt)*) => {
+        simulacrum_qualify_self!(@munch $trait_path $callback [(paren $result ($($tail)*)) $($stack)*] () $($group)*)
+    };
+    (@munch $trait_path:tt $callback:tt [$($stack:tt)*] $result:tt [$($group:tt)*] $($tail:tt)*) => {
+        simulacrum_qualify_self!(@munch $trait_path $callback [(bracket $result ($($tail)*)) $($stack)*] () $($group)*)
+    };
+    (@munch $trait_path:tt $callback:tt $stack:tt ($($result:tt)*) $token:tt $($tail:tt)*) => {
+        simulacrum_qualify_self!(@munch $trait_path $callback $stack ($($result)* $token) $($tail)*)
+    };
+
+    // End of a group, so continue after it.
+    (@munch $trait_path:tt $callback:tt [(paren ($($outer:tt)*) ($($tail:tt)*)) $($stack:tt)*] ($($result:tt)*)) => {
+        simulacrum_qualify_self!(@munch $trait_path $callback [$($stack)*] ($($outer)* ($($result)*)) $($tail)*)
+    };
+    (@munch $trait_path:tt $callback:tt [(bracket ($($outer:tt)*) ($($tail:tt)*)) $($stack:tt)*] ($($result:tt)*)) => {
+        simulacrum_qualify_self!(@munch $trait_path $callback [$($stack)*] ($($outer)* [$($result)*]) $($tail)*)
+    };
+
+    // Done
+    (@munch $trait_path:tt [$callback:ident [$($prefix:tt)*] [$($suffix:tt)*]] [] ($($result:tt)*)) => {
+        $callback!($($prefix)* $($result)* $($suffix)*)
+    };
+
+    // main
+    (($($trait_path:tt)*) $callback:ident [$($prefix:tt)*] [$($suffix:tt)*] $($tokens:tt)*) => {
+        simulacrum_qualify_self!(@munch ($($trait_path)*) [$callback [$($prefix)*] [$($suffix)*]] [] () $($tokens)*)
     };
 }
 
 #[macro_export]
 macro_rules! create_mock_struct {
     (@create_expect_methods) => {};
+    (@create_expect_methods $name:ident [$($generics:tt)*] ($key:expr) $inputs:ty => $output:ty; $($tail:tt)*) => {
+        create_expect_method!($name [$($generics)*] ($key) $inputs => $output);
+        create_mock_struct!(@create_expect_methods $($tail)*);
+    };
+    (@create_expect_methods $name:ident [$($generics:tt)*] ($key:expr) $inputs:ty; $($tail:tt)*) => {
+        create_expect_method!($name [$($generics)*] ($key) $inputs);
+        create_mock_struct!(@create_expect_methods $($tail)*);
+    };
+    // Other methods use the typed keys from `@create_method_keys`.
     (@create_expect_methods $name:ident($key:expr) $inputs:ty => $output:ty; $($tail:tt)*) => {
-        create_expect_method!($name($key) $inputs => $output);
+        #[allow(non_snake_case)]
+        pub fn $name(&mut self) -> $crate::Method<$inputs, $output> {
+            self.e.expect::<$inputs, $output>($crate::MockKey::<Self, { $crate::__private::method_id($key) }>::new())
+        }
         create_mock_struct!(@create_expect_methods $($tail)*);
     };
     (@create_expect_methods $name:ident($key:expr) $inputs:ty; $($tail:tt)*) => {
-        create_expect_method!($name($key) $inputs);
-        create_mock_struct!(@create_expect_methods $($tail)*);
+        create_mock_struct!(@create_expect_methods $name($key) $inputs => (); $($tail)*);
     };
     (@create_expect_methods $name:ident($key:expr); $($tail:tt)*) => {
-        create_expect_method!($name($key));
-        create_mock_struct!(@create_expect_methods $($tail)*);
+        create_mock_struct!(@create_expect_methods $name($key) () => (); $($tail)*);
+    };
+    // Implement `MockMethod` for each method that isn't generic, so that it
+    // gets a `MockKey` that only fits its types.
+    (@create_method_keys $name:ident $lifetimes:tt $params:tt) => {};
+    (@create_method_keys $name:ident $lifetimes:tt $params:tt $expect_name:ident [$($generics:tt)*] ($key:expr) $inputs:ty $(=> $output:ty)*; $($tail:tt)*) => {
+        create_mock_struct!(@create_method_keys $name $lifetimes $params $($tail)*);
+    };
+    (@create_method_keys $name:ident [$($lifetime:lifetime)*] [$($param:ident)*] $expect_name:ident($key:expr) $inputs:ty => $output:ty; $($tail:tt)*) => {
+        impl<$($lifetime,)* $($param: 'static),*> $crate::__private::MockMethod<$inputs, $output, { $crate::__private::method_id($key) }> for $name<$($lifetime,)* $($param),*> {
+            const NAME: &'static str = $key;
+
+            fn params_formatter() -> Option<fn(&$inputs) -> String> {
+                use $crate::__private::{FormatDebug as _, FormatUnknown as _};
+                (&$crate::__private::DebugParams::<$inputs>::new()).formatter()
+            }
+        }
+        create_mock_struct!(@create_method_keys $name [$($lifetime)*] [$($param)*] $($tail)*);
+    };
+    (@create_method_keys $name:ident $lifetimes:tt $params:tt $expect_name:ident($key:expr) $inputs:ty; $($tail:tt)*) => {
+        create_mock_struct!(@create_method_keys $name $lifetimes $params $expect_name($key) $inputs => (); $($tail)*);
+    };
+    (@create_method_keys $name:ident $lifetimes:tt $params:tt $expect_name:ident($key:expr); $($tail:tt)*) => {
+        create_mock_struct!(@create_method_keys $name $lifetimes $params $expect_name($key) () => (); $($tail)*);
+    };
+    // Sort generic parameters into lifetimes and types, since only types need
+    // to be `'static`.
+    (@sort_params $name:ident [$($lifetime:tt)*] [$($param:tt)*] ($next:lifetime $($rest:tt)*) $methods:tt) => {
+        create_mock_struct!(@sort_params $name [$($lifetime)* $next] [$($param)*] ($($rest)*) $methods);
+    };
+    (@sort_params $name:ident [$($lifetime:tt)*] [$($param:tt)*] ($next:ident $($rest:tt)*) $methods:tt) => {
+        create_mock_struct!(@sort_params $name [$($lifetime)*] [$($param)* $next] ($($rest)*) $methods);
+    };
+    (@sort_params $name:ident [$($lifetime:tt)*] [$($param:tt)*] () { $($methods:tt)* }) => {
+        create_mock_struct!(@struct $name [$($lifetime)*] [$($param)*] { $($methods)* });
     };
     (struct $name:ident: {
         $($methods:tt)*
+    }) => {
+        create_mock_struct!(@struct $name [] [] { $($methods)* });
+    };
+    (struct $name:ident <>: {
+        $($methods:tt)*
+    }) => {
+        create_mock_struct!(@struct $name [] [] { $($methods)* });
+    };
+    (struct $name:ident < $($param:tt),* >: {
+        $($methods:tt)*
+    }) => {
+        create_mock_struct!(@sort_params $name [] [] ($($param)*) { $($methods)* });
+    };
+    (@struct $name:ident [$($lifetime:lifetime)*] [$($param:ident)*] {
+        $($methods:tt)*
     }) => {
         #[allow(non_snake_case)]
-        pub struct $name {
-            e: $crate::Expectations
+        pub struct $name<$($lifetime,)* $($param),*> {
+            e: $crate::Expectations,
+            _params: ::std::marker::PhantomData<fn() -> ($(&$lifetime (),)* $($param,)*)>
         }
 
         #[allow(dead_code)]
         #[allow(non_snake_case)]
-        impl $name {
+        impl<$($lifetime,)* $($param: 'static),*> $name<$($lifetime,)* $($param),*> {
             pub fn new() -> Self {
                 Self {
-                    e: $crate::Expectations::new()
+                    e: $crate::Expectations::with_name(stringify!($name)),
+                    _params: ::std::marker::PhantomData
+                }
+            }
+
+            /// Same as `new()`, but the mock's expectations are also used by
+            /// the type's functions without `self` on this thread, until it's
+            /// dropped.
+            pub fn with_statics() -> Self where Self: 'static {
+                Self {
+                    e: $crate::Expectations::with_name(stringify!($name)).install_static::<Self>(),
+                    _params: ::std::marker::PhantomData
                 }
             }
 
@@ -153,131 +439,770 @@ macro_rules! create_mock_struct {
                 self
             }
 
+            pub fn strict(&mut self) -> &mut Self {
+                self.e.strict();
+                self
+            }
+
+            pub fn verify_all(&self) -> Result<(), $crate::VerificationReport> {
+                self.e.verify_all()
+            }
+
+            pub fn checkpoint(&mut self) -> Result<(), $crate::VerificationReport> {
+                self.e.checkpoint()
+            }
+
+            pub fn record_calls(&mut self) -> &mut Self {
+                self.e.record_calls();
+                self
+            }
+
+            pub fn record_params<I>(&mut self, name: &str) -> &mut Self where
+                I: Clone + $crate::MaybeSend + 'static
+            {
+                self.e.record_params::<I>(name);
+                self
+            }
+
+            pub fn call_log(&self) -> Vec<$crate::CallRecord> {
+                self.e.call_log()
+            }
+
+            pub fn calls<I>(&self, name: &str) -> Vec<I> where
+                I: Clone + 'static
+            {
+                self.e.calls::<I>(name)
+            }
+
             create_mock_struct!(@create_expect_methods $($methods)*);
         }
 
-        impl Default for $name {
+        impl<$($lifetime,)* $($param: 'static),*> Default for $name<$($lifetime,)* $($param),*> {
             fn default() -> Self {
                 Self::new()
             }
         }
+
+        create_mock_struct!(@create_method_keys $name [$($lifetime)*] [$($param)*] $($methods)*);
+    };
+}
+
+/// Use this macro to mock free functions. In tests, the functions check the
+/// expectations of the given mock struct instead of running their bodies.
+#[macro_export]
+macro_rules! create_mock_fns {
+    (@fns $mock:ident ($($result:tt)*)) => {
+        #[cfg(test)]
+        create_mock_struct! {
+            struct $mock: {
+                $($result)*
+            }
+        }
+    };
+    (@fns $mock:ident ($($result:tt)*)
+        $expect_name:ident($key:expr):
+        $(#[$attr:meta])*
+        $vis:vis fn $fn_name:ident $sig:tt -> $output:ty $body:block
+        $($tail:tt)*
+    ) => {
+        $(#[$attr])*
+        #[cfg(not(test))]
+        $vis fn $fn_name $sig -> $output $body
+
+        $(#[$attr])*
+        #[cfg(test)]
+        $vis fn $fn_name $sig -> $output {
+            was_called!(<$mock>, $crate::MockKey::<$mock, { $crate::__private::method_id($key) }>::new(), $sig -> $output)
+        }
+
+        create_mock_fns!(@fns $mock (
+            $($result)*
+            $expect_name($key) simulacrum_tuplefy!(kind $sig -> ()) => $output;
+        ) $($tail)*);
+    };
+    (@fns $mock:ident ($($result:tt)*)
+        $expect_name:ident($key:expr):
+        $(#[$attr:meta])*
+        $vis:vis fn $fn_name:ident $sig:tt $body:block
+        $($tail:tt)*
+    ) => {
+        $(#[$attr])*
+        #[cfg(not(test))]
+        $vis fn $fn_name $sig $body
+
+        $(#[$attr])*
+        #[cfg(test)]
+        $vis fn $fn_name $sig {
+            was_called!(<$mock>, $crate::MockKey::<$mock, { $crate::__private::method_id($key) }>::new(), $sig)
+        }
+
+        create_mock_fns!(@fns $mock (
+            $($result)*
+            $expect_name($key) simulacrum_tuplefy!(kind $sig -> ());
+        ) $($tail)*);
+    };
+
+    // main
+    (struct $mock:ident; $($tail:tt)*) => {
+        create_mock_fns!(@fns $mock () $($tail)*);
     };
 }
 
 #[macro_export]
 macro_rules! create_mock {
+    // split_generics
+    // Collect the generic parameters of a method up to the `>` before its
+    // parameter list, then pass them to `$callback` as `[T: Bound]`.
+    (@split_generics ($($callback:tt)*) [$($generics:tt)*] > ($($params:tt)*) $($tail:tt)*) => {
+        create_mock!($($callback)* [$($generics)*] ($($params)*) $($tail)*);
+    };
+    (@split_generics ($($callback:tt)*) [$($generics:tt)*] >> ($($params:tt)*) $($tail:tt)*) => {
+        create_mock!($($callback)* [$($generics)* >] ($($params)*) $($tail)*);
+    };
+    (@split_generics ($($callback:tt)*) [$($generics:tt)*] $token:tt $($tail:tt)*) => {
+        create_mock!(@split_generics ($($callback)*) [$($generics)* $token] $($tail)*);
+    };
+
+    // take_type
+    // Collect the tokens of a type up to the next `;`, a few at a time, then
+    // pass them to `$callback` as `[Type]`.
+    (@take_type ($($callback:tt)*) [$($taken:tt)*] ; $($tail:tt)*) => {
+        create_mock!($($callback)* [$($taken)*] $($tail)*);
+    };
+    (@take_type ($($callback:tt)*) [$($taken:tt)*] $t0:tt ; $($tail:tt)*) => {
+        create_mock!($($callback)* [$($taken)* $t0] $($tail)*);
+    };
+    (@take_type ($($callback:tt)*) [$($taken:tt)*] $t0:tt $t1:tt ; $($tail:tt)*) => {
+        create_mock!($($callback)* [$($taken)* $t0 $t1] $($tail)*);
+    };
+    (@take_type ($($callback:tt)*) [$($taken:tt)*] $t0:tt $t1:tt $t2:tt ; $($tail:tt)*) => {
+        create_mock!($($callback)* [$($taken)* $t0 $t1 $t2] $($tail)*);
+    };
+    (@take_type ($($callback:tt)*) [$($taken:tt)*] $t0:tt $t1:tt $t2:tt $t3:tt ; $($tail:tt)*) => {
+        create_mock!($($callback)* [$($taken)* $t0 $t1 $t2 $t3] $($tail)*);
+    };
+    (@take_type ($($callback:tt)*) [$($taken:tt)*] $t0:tt $t1:tt $t2:tt $t3:tt $t4:tt ; $($tail:tt)*) => {
+        create_mock!($($callback)* [$($taken)* $t0 $t1 $t2 $t3 $t4] $($tail)*);
+    };
+    (@take_type ($($callback:tt)*) [$($taken:tt)*] $t0:tt $t1:tt $t2:tt $t3:tt $t4:tt $t5:tt ; $($tail:tt)*) => {
+        create_mock!($($callback)* [$($taken)* $t0 $t1 $t2 $t3 $t4 $t5] $($tail)*);
+    };
+    (@take_type ($($callback:tt)*) [$($taken:tt)*] $t0:tt $t1:tt $t2:tt $t3:tt $t4:tt $t5:tt $t6:tt ; $($tail:tt)*) => {
+        create_mock!($($callback)* [$($taken)* $t0 $t1 $t2 $t3 $t4 $t5 $t6] $($tail)*);
+    };
+    (@take_type $callback:tt [$($taken:tt)*] $t0:tt $t1:tt $t2:tt $t3:tt $t4:tt $t5:tt $t6:tt $t7:tt $($tail:tt)*) => {
+        create_mock!(@take_type $callback [$($taken)* $t0 $t1 $t2 $t3 $t4 $t5 $t6 $t7] $($tail)*);
+    };
+
     // create_mock_struct
-    (@create_mock_struct($mock_name:ident, ()) -> ($($result:tt)*)) => {
+    (@create_mock_struct([$($mock_name:tt)*], $qualify:tt, ()) -> ($($result:tt)*)) => {
         create_mock_struct! {
-            struct $mock_name: {
+            struct $($mock_name)*: {
                 $($result)*
             }
         }
     };
     (@create_mock_struct
-        ($mock_name:ident, (
+        ($mock_name:tt, $qualify:tt, (
+            $expect_name:ident($key:expr):
+            $(unsafe)* fn $method_name:ident < $($tail:tt)*
+        )) -> ($($result:tt)*)
+    ) => {
+        create_mock!(@split_generics (@create_mock_struct_generic ($mock_name, $qualify, $expect_name($key)) -> ($($result)*)) [] $($tail)*);
+    };
+    (@create_mock_struct_generic
+        ($mock_name:tt, $qualify:tt, $expect_name:ident($key:expr)) -> ($($result:tt)*)
+        [$($generics:tt)*] $sig:tt $(-> $output:ty)*;
+        $($tail:tt)*
+    ) => {
+        create_mock!(@create_mock_struct ($mock_name, $qualify, ($($tail)*)) -> (
+            $($result)* 
+            $expect_name [$($generics)*] ($key) simulacrum_tuplefy!(kind $sig -> ()) $(=> $output)*;
+        ));
+    };
+    // When the mock binds associated types, `Self::Name` in the parameter and
+    // return types is qualified with the trait, since the expect methods live
+    // outside of the trait's impl. The return type is kept as tokens for that.
+    (@create_mock_struct
+        ($mock_name:tt, [$($trait_path:tt)+], (
+            $expect_name:ident($key:expr):
+            $(async)* $(unsafe)* fn $method_name:ident $sig:tt;
+            $($tail:tt)*
+        )) -> ($($result:tt)*)
+    ) => {
+        create_mock!(@create_mock_struct ($mock_name, [$($trait_path)+], ($($tail)*)) -> (
+            $($result)* 
+            $expect_name($key) simulacrum_qualify_self!(($($trait_path)+) simulacrum_tuplefy [kind] [-> ()] $sig);
+        ));
+    };
+    (@create_mock_struct
+        ($mock_name:tt, [$($trait_path:tt)+], (
+            $expect_name:ident($key:expr):
+            $(async)* $(unsafe)* fn $method_name:ident $sig:tt -> $($tail:tt)*
+        )) -> ($($result:tt)*)
+    ) => {
+        create_mock!(@take_type (@create_mock_struct_qualified ($mock_name, [$($trait_path)+], $expect_name($key), $sig) -> ($($result)*)) [] $($tail)*);
+    };
+    (@create_mock_struct_qualified
+        ($mock_name:tt, [$($trait_path:tt)+], $expect_name:ident($key:expr), $sig:tt) -> ($($result:tt)*)
+        [$($output:tt)*] $($tail:tt)*
+    ) => {
+        create_mock!(@create_mock_struct ($mock_name, [$($trait_path)+], ($($tail)*)) -> (
+            $($result)* 
+            $expect_name($key) simulacrum_qualify_self!(($($trait_path)+) simulacrum_tuplefy [kind] [-> ()] $sig)
+                => simulacrum_qualify_self!(($($trait_path)+) simulacrum_tuplefy [@as_ty] [] $($output)*);
+        ));
+    };
+    (@create_mock_struct
+        ($mock_name:tt, $qualify:tt, (
+            $expect_name:ident($key:expr):
+            async fn $method_name:ident $sig:tt;
+            $($tail:tt)*
+        )) -> ($($result:tt)*)
+    ) => {
+        create_mock!(@create_mock_struct ($mock_name, $qualify, ($($tail)*)) -> (
+            $($result)* 
+            $expect_name($key) simulacrum_tuplefy!(kind $sig -> ());
+        ));
+    };
+    (@create_mock_struct
+        ($mock_name:tt, $qualify:tt, (
+            $expect_name:ident($key:expr):
+            async fn $method_name:ident $sig:tt -> $output:ty;
+            $($tail:tt)*
+        )) -> ($($result:tt)*)
+    ) => {
+        create_mock!(@create_mock_struct ($mock_name, $qualify, ($($tail)*)) -> (
+            $($result)* 
+            $expect_name($key) simulacrum_tuplefy!(kind $sig -> ()) => $output;
+        ));
+    };
+    // `>>>` is lexed as `>>` and `>`, so futures without extra bounds need
+    // their own rules.
+    (@create_mock_struct
+        ($mock_name:tt, $qualify:tt, (
+            $expect_name:ident($key:expr):
+            fn $method_name:ident $sig:tt -> Pin<Box<dyn Future<Output = $output:ty>>>;
+            $($tail:tt)*
+        )) -> ($($result:tt)*)
+    ) => {
+        create_mock!(@create_mock_struct ($mock_name, $qualify, ($($tail)*)) -> (
+            $($result)* 
+            $expect_name($key) simulacrum_tuplefy!(kind $sig -> ()) => $output;
+        ));
+    };
+    (@create_mock_struct
+        ($mock_name:tt, $qualify:tt, (
+            $expect_name:ident($key:expr):
+            fn $method_name:ident $sig:tt -> Pin<Box<dyn Future<Output = $output:ty> $(+ $bound:tt)*>>;
+            $($tail:tt)*
+        )) -> ($($result:tt)*)
+    ) => {
+        create_mock!(@create_mock_struct ($mock_name, $qualify, ($($tail)*)) -> (
+            $($result)* 
+            $expect_name($key) simulacrum_tuplefy!(kind $sig -> ()) => $output;
+        ));
+    };
+    (@create_mock_struct
+        ($mock_name:tt, $qualify:tt, (
+            $expect_name:ident($key:expr):
+            fn $method_name:ident $sig:tt -> impl Future<Output = $output:ty> $(+ $bound:tt)*;
+            $($tail:tt)*
+        )) -> ($($result:tt)*)
+    ) => {
+        create_mock!(@create_mock_struct ($mock_name, $qualify, ($($tail)*)) -> (
+            $($result)* 
+            $expect_name($key) simulacrum_tuplefy!(kind $sig -> ()) => $output;
+        ));
+    };
+    (@create_mock_struct
+        ($mock_name:tt, $qualify:tt, (
             $expect_name:ident($key:expr):
             fn $method_name:ident $sig:tt;
             $($tail:tt)*
         )) -> ($($result:tt)*)
     ) => {
-        create_mock!(@create_mock_struct ($mock_name, ($($tail)*)) -> (
+        create_mock!(@create_mock_struct ($mock_name, $qualify, ($($tail)*)) -> (
             $($result)* 
             $expect_name($key) simulacrum_tuplefy!(kind $sig -> ());
         ));
     };
     (@create_mock_struct
-        ($mock_name:ident, (
+        ($mock_name:tt, $qualify:tt, (
             $expect_name:ident($key:expr):
             fn $method_name:ident $sig:tt -> $output:ty;
             $($tail:tt)*
         )) -> ($($result:tt)*)
     ) => {
-        create_mock!(@create_mock_struct ($mock_name, ($($tail)*)) -> (
+        create_mock!(@create_mock_struct ($mock_name, $qualify, ($($tail)*)) -> (
             $($result)* 
             $expect_name($key) simulacrum_tuplefy!(kind $sig -> ()) => $output;
         ));
     };
     (@create_mock_struct
-        ($mock_name:ident, (
+        ($mock_name:tt, $qualify:tt, (
             $expect_name:ident($key:expr):
             unsafe fn $method_name:ident $sig:tt;
             $($tail:tt)*
         )) -> ($($result:tt)*)
     ) => {
-        create_mock!(@create_mock_struct ($mock_name, ($($tail)*)) -> (
+        create_mock!(@create_mock_struct ($mock_name, $qualify, ($($tail)*)) -> (
             $($result)* 
             $expect_name($key) simulacrum_tuplefy!(kind $sig -> ());
         ));
     };
     (@create_mock_struct
-        ($mock_name:ident, (
+        ($mock_name:tt, $qualify:tt, (
             $expect_name:ident($key:expr):
             unsafe fn $method_name:ident $sig:tt -> $output:ty;
             $($tail:tt)*
         )) -> ($($result:tt)*)
     ) => {
-        create_mock!(@create_mock_struct ($mock_name, ($($tail)*)) -> (
+        create_mock!(@create_mock_struct ($mock_name, $qualify, ($($tail)*)) -> (
             $($result)* 
             $expect_name($key) simulacrum_tuplefy!(kind $sig -> ()) => $output;
         ));
     };
 
+    // key
+    // The typed key of a method of the mock, from `create_mock_struct!`.
+    (@key $key:expr) => {
+        $crate::MockKey::<Self, { $crate::__private::method_id($key) }>::new()
+    };
+
+    // was_called
+    // Functions without `self` use the expectations installed for the mock's type.
+    (@was_called $self_:ident (& $($param:tt)*), $($args:tt)*) => {
+        was_called!($self_, $($args)*)
+    };
+    (@was_called $self_:ident (self $($param:tt)*), $($args:tt)*) => {
+        was_called!($self_, $($args)*)
+    };
+    (@was_called $self_:ident (mut self $($param:tt)*), $($args:tt)*) => {
+        was_called!($self_, $($args)*)
+    };
+    (@was_called $self_:ident $sig:tt, $($args:tt)*) => {
+        was_called!(Self, $($args)*)
+    };
+
     // create_stub_methods
-    (@create_stub_methods ($self_:ident)) => {};
-    (@create_stub_methods ($self_:ident)
+    (@create_stub_methods ($self_:ident, [$($vis:tt)*])) => {};
+    (@create_stub_methods ($self_:ident, [$($vis:tt)*])
+        $expect_name:ident($key:expr):
+        fn $method_name:ident < $($tail:tt)*
+    ) => {
+        create_mock!(@split_generics (@create_stub_generic ($self_, [$($vis)*], $key, $method_name) ()) [] $($tail)*);
+    };
+    (@create_stub_methods ($self_:ident, [$($vis:tt)*])
+        $expect_name:ident($key:expr):
+        unsafe fn $method_name:ident < $($tail:tt)*
+    ) => {
+        create_mock!(@split_generics (@create_stub_generic ($self_, [$($vis)*], $key, $method_name) (unsafe)) [] $($tail)*);
+    };
+    (@create_stub_generic ($self_:ident, [$($vis:tt)*], $key:expr, $method_name:ident) ($($unsafe:tt)*)
+        [$($generics:tt)*] $sig:tt;
+        $($tail:tt)*
+    ) => {
+        #[allow(warnings)]
+        $($vis)* $($unsafe)* fn $method_name<$($generics)*> $sig {
+            create_mock!(@was_called $self_ $sig, $crate::MethodKey::generic($key), $sig)
+        }
+        create_mock!(@create_stub_methods ($self_, [$($vis)*]) $($tail)*);
+    };
+    (@create_stub_generic ($self_:ident, [$($vis:tt)*], $key:expr, $method_name:ident) ($($unsafe:tt)*)
+        [$($generics:tt)*] $sig:tt -> $output:ty;
+        $($tail:tt)*
+    ) => {
+        #[allow(warnings)]
+        $($vis)* $($unsafe)* fn $method_name<$($generics)*> $sig -> $output {
+            create_mock!(@was_called $self_ $sig, $crate::MethodKey::generic($key), $sig -> $output)
+        }
+        create_mock!(@create_stub_methods ($self_, [$($vis)*]) $($tail)*);
+    };
+    (@create_stub_methods ($self_:ident, [$($vis:tt)*])
+        $expect_name:ident($key:expr):
+        async fn $method_name:ident $sig:tt;
+        $($tail:tt)*
+    ) => {
+        #[allow(warnings)]
+        $($vis)* fn $method_name $sig -> impl $crate::__private::Future<Output = ()> {
+            create_mock!(@was_called $self_ $sig, create_mock!(@key $key), $sig -> async ())
+        }
+        create_mock!(@create_stub_methods ($self_, [$($vis)*]) $($tail)*);
+    };
+    (@create_stub_methods ($self_:ident, [$($vis:tt)*])
+        $expect_name:ident($key:expr):
+        async fn $method_name:ident $sig:tt -> $output:ty;
+        $($tail:tt)*
+    ) => {
+        #[allow(warnings)]
+        $($vis)* fn $method_name $sig -> impl $crate::__private::Future<Output = $output> {
+            create_mock!(@was_called $self_ $sig, create_mock!(@key $key), $sig -> async $output)
+        }
+        create_mock!(@create_stub_methods ($self_, [$($vis)*]) $($tail)*);
+    };
+    (@create_stub_methods ($self_:ident, [$($vis:tt)*])
+        $expect_name:ident($key:expr):
+        fn $method_name:ident $sig:tt -> Pin<Box<dyn Future<Output = $output:ty>>>;
+        $($tail:tt)*
+    ) => {
+        #[allow(warnings)]
+        $($vis)* fn $method_name $sig -> $crate::__private::Pin<$crate::__private::Box<dyn $crate::__private::Future<Output = $output>>> {
+            $crate::__private::Box::pin(create_mock!(@was_called $self_ $sig, create_mock!(@key $key), $sig -> async $output))
+        }
+        create_mock!(@create_stub_methods ($self_, [$($vis)*]) $($tail)*);
+    };
+    (@create_stub_methods ($self_:ident, [$($vis:tt)*])
+        $expect_name:ident($key:expr):
+        fn $method_name:ident $sig:tt -> Pin<Box<dyn Future<Output = $output:ty> $(+ $bound:tt)*>>;
+        $($tail:tt)*
+    ) => {
+        #[allow(warnings)]
+        $($vis)* fn $method_name $sig -> $crate::__private::Pin<$crate::__private::Box<dyn $crate::__private::Future<Output = $output> $(+ $bound)*>> {
+            $crate::__private::Box::pin(create_mock!(@was_called $self_ $sig, create_mock!(@key $key), $sig -> async $output))
+        }
+        create_mock!(@create_stub_methods ($self_, [$($vis)*]) $($tail)*);
+    };
+    (@create_stub_methods ($self_:ident, [$($vis:tt)*])
+        $expect_name:ident($key:expr):
+        fn $method_name:ident $sig:tt -> impl Future<Output = $output:ty> $(+ $bound:tt)*;
+        $($tail:tt)*
+    ) => {
+        #[allow(warnings)]
+        $($vis)* fn $method_name $sig -> impl $crate::__private::Future<Output = $output> $(+ $bound)* {
+            create_mock!(@was_called $self_ $sig, create_mock!(@key $key), $sig -> async $output)
+        }
+        create_mock!(@create_stub_methods ($self_, [$($vis)*]) $($tail)*);
+    };
+    (@create_stub_methods ($self_:ident, [$($vis:tt)*])
         $expect_name:ident($key:expr):
         fn $method_name:ident $sig:tt;
         $($tail:tt)*
     ) => {
         #[allow(warnings)]
-        fn $method_name $sig {
-            was_called!($self_, $key, $sig)
+        $($vis)* fn $method_name $sig {
+            create_mock!(@was_called $self_ $sig, create_mock!(@key $key), $sig)
         }
-        create_mock!(@create_stub_methods ($self_) $($tail)*);
+        create_mock!(@create_stub_methods ($self_, [$($vis)*]) $($tail)*);
     };
-    (@create_stub_methods ($self_:ident)
+    (@create_stub_methods ($self_:ident, [$($vis:tt)*])
         $expect_name:ident($key:expr):
         fn $method_name:ident $sig:tt -> $output:ty;
         $($tail:tt)*
     ) => {
         #[allow(warnings)]
-        fn $method_name $sig -> $output {
-            was_called!($self_, $key, $sig -> $output)
+        $($vis)* fn $method_name $sig -> $output {
+            create_mock!(@was_called $self_ $sig, create_mock!(@key $key), $sig -> $output)
         }
-        create_mock!(@create_stub_methods ($self_) $($tail)*);
+        create_mock!(@create_stub_methods ($self_, [$($vis)*]) $($tail)*);
     };
-    (@create_stub_methods ($self_:ident)
+    (@create_stub_methods ($self_:ident, [$($vis:tt)*])
         $expect_name:ident($key:expr):
         unsafe fn $method_name:ident $sig:tt;
         $($tail:tt)*
     ) => {
         #[allow(warnings)]
-        unsafe fn $method_name $sig {
-            was_called!($self_, $key, $sig)
+        $($vis)* unsafe fn $method_name $sig {
+            create_mock!(@was_called $self_ $sig, create_mock!(@key $key), $sig)
         }
-        create_mock!(@create_stub_methods ($self_) $($tail)*);
+        create_mock!(@create_stub_methods ($self_, [$($vis)*]) $($tail)*);
     };
-    (@create_stub_methods ($self_:ident)
+    (@create_stub_methods ($self_:ident, [$($vis:tt)*])
         $expect_name:ident($key:expr):
         unsafe fn $method_name:ident $sig:tt -> $output:ty;
         $($tail:tt)*
     ) => {
         #[allow(warnings)]
-        unsafe fn $method_name $sig -> $output {
-            was_called!($self_, $key, $sig -> $output)
+        $($vis)* unsafe fn $method_name $sig -> $output {
+            create_mock!(@was_called $self_ $sig, create_mock!(@key $key), $sig -> $output)
+        }
+        create_mock!(@create_stub_methods ($self_, [$($vis)*]) $($tail)*);
+    };
+
+    // create_delegates
+    (@create_delegates ($self_:ident, $inner:ident, $qualify:tt)) => {};
+    // Generic methods can't be forwarded, since each call may use new types.
+    (@create_delegates ($self_:ident, $inner:ident, $qualify:tt)
+        $expect_name:ident($key:expr):
+        $(unsafe)* fn $method_name:ident < $($tail:tt)*
+    ) => {
+        create_mock!(@split_generics (@skip_generic_delegate ($self_, $inner, $qualify)) [] $($tail)*);
+    };
+    (@skip_generic_delegate ($self_:ident, $inner:ident, $qualify:tt)
+        [$($generics:tt)*] $sig:tt $(-> $output:ty)*;
+        $($tail:tt)*
+    ) => {
+        create_mock!(@create_delegates ($self_, $inner, $qualify) $($tail)*);
+    };
+    // Futures can't be forwarded, since they borrow the inner object.
+    (@create_delegates ($self_:ident, $inner:ident, $qualify:tt)
+        $expect_name:ident($key:expr):
+        async fn $method_name:ident $sig:tt $(-> $output:ty)*;
+        $($tail:tt)*
+    ) => {
+        create_mock!(@create_delegates ($self_, $inner, $qualify) $($tail)*);
+    };
+    (@create_delegates ($self_:ident, $inner:ident, $qualify:tt)
+        $expect_name:ident($key:expr):
+        fn $method_name:ident $sig:tt -> Pin<Box<dyn Future<Output = $output:ty>>>;
+        $($tail:tt)*
+    ) => {
+        create_mock!(@create_delegates ($self_, $inner, $qualify) $($tail)*);
+    };
+    (@create_delegates ($self_:ident, $inner:ident, $qualify:tt)
+        $expect_name:ident($key:expr):
+        fn $method_name:ident $sig:tt -> Pin<Box<dyn Future<Output = $output:ty> $(+ $bound:tt)*>>;
+        $($tail:tt)*
+    ) => {
+        create_mock!(@create_delegates ($self_, $inner, $qualify) $($tail)*);
+    };
+    (@create_delegates ($self_:ident, $inner:ident, $qualify:tt)
+        $expect_name:ident($key:expr):
+        fn $method_name:ident $sig:tt -> impl Future<Output = $output:ty> $(+ $bound:tt)*;
+        $($tail:tt)*
+    ) => {
+        create_mock!(@create_delegates ($self_, $inner, $qualify) $($tail)*);
+    };
+    (@create_delegates ($self_:ident, $inner:ident, $qualify:tt)
+        $expect_name:ident($key:expr):
+        fn $method_name:ident $sig:tt $(-> $output:ty)*;
+        $($tail:tt)*
+    ) => {
+        create_mock!(@create_delegate ($self_, $inner, $qualify, $key, $method_name, $sig) ());
+        create_mock!(@create_delegates ($self_, $inner, $qualify) $($tail)*);
+    };
+    (@create_delegates ($self_:ident, $inner:ident, $qualify:tt)
+        $expect_name:ident($key:expr):
+        unsafe fn $method_name:ident $sig:tt $(-> $output:ty)*;
+        $($tail:tt)*
+    ) => {
+        create_mock!(@create_delegate ($self_, $inner, $qualify, $key, $method_name, $sig) (unsafe));
+        create_mock!(@create_delegates ($self_, $inner, $qualify) $($tail)*);
+    };
+    // Only methods taking `&self` or `&mut self` are forwarded, since `inner`
+    // is shared and can't be consumed. Functions without `self` don't use it.
+    (@create_delegate ($self_:ident, $inner:ident, $qualify:tt, $key:expr, $method_name:ident, (& $($param:tt)*)) $unsafe:tt) => {
+        create_mock!(@check_delegate_lifetimes ($self_, $inner, $qualify, $key, $method_name, (& $($param)*)) $unsafe ($($param)*));
+    };
+    (@create_delegate ($self_:ident, $inner:ident, $qualify:tt, $key:expr, $method_name:ident, $sig:tt) $unsafe:tt) => {};
+    // Params borrowed for other named lifetimes might be kept beyond the call,
+    // so they can't be passed on from a Borrowed. Methods taking them aren't
+    // forwarded either.
+    (@check_delegate_lifetimes $info:tt $unsafe:tt ()) => {
+        create_mock!(@create_delegate_method $info $unsafe);
+    };
+    (@check_delegate_lifetimes $info:tt $unsafe:tt (& 'static $($tail:tt)*)) => {
+        create_mock!(@check_delegate_lifetimes $info $unsafe ($($tail)*));
+    };
+    (@check_delegate_lifetimes $info:tt $unsafe:tt (& $lifetime:lifetime $($tail:tt)*)) => {};
+    (@check_delegate_lifetimes $info:tt $unsafe:tt ($head:tt $($tail:tt)*)) => {
+        create_mock!(@check_delegate_lifetimes $info $unsafe ($($tail)*));
+    };
+    (@create_delegate_method ($self_:ident, $inner:ident, [], $key:expr, $method_name:ident, $sig:tt) ($($unsafe:tt)*)) => {
+        {
+            let $inner = $inner.clone();
+            #[allow(unused_parens)]
+            $self_.e.delegate::<simulacrum_tuplefy!(kind $sig -> ()), _, _>(create_mock!(@key $key), move |simulacrum_tuplefy!(pat $sig -> ())| $($unsafe)* {
+                simulacrum_tuplefy!(call ($inner.lock().unwrap().$method_name) $sig)
+            });
+        }
+    };
+    (@create_delegate_method ($self_:ident, $inner:ident, [$($trait_path:tt)+], $key:expr, $method_name:ident, $sig:tt) ($($unsafe:tt)*)) => {
+        {
+            let $inner = $inner.clone();
+            #[allow(unused_parens)]
+            $self_.e.delegate::<simulacrum_qualify_self!(($($trait_path)+) simulacrum_tuplefy [kind] [-> ()] $sig), _, _>(create_mock!(@key $key), move |simulacrum_tuplefy!(pat $sig -> ())| $($unsafe)* {
+                simulacrum_tuplefy!(call ($inner.lock().unwrap().$method_name) $sig)
+            });
         }
-        create_mock!(@create_stub_methods ($self_) $($tail)*);
     };
 
     // main
+    // Mock an inherent impl, so that the mock can stand in for a struct.
+    (impl $mock_name:ident ($self_:ident) {
+        $($method_info:tt)*
+    }) => {
+        create_mock!(@create_mock_struct ([$mock_name], [], ($($method_info)*)) -> ());
+
+        #[allow(dead_code)]
+        impl $mock_name {
+            create_mock!(@create_stub_methods ($self_, [pub]) $($method_info)*);
+        }
+    };
+    // Mock several traits with one mock object, so that they share its
+    // expectations.
+    (struct $mock_name:ident ($self_:ident) {
+        $(impl $trait_name:ident { $($method_info:tt)* })*
+    }) => {
+        create_mock!(@traits $mock_name ($self_) [] [$($trait_name { $($method_info)* })*]);
+    };
     (impl $trait_name:ident for $mock_name:ident ($self_:ident) {
         $($method_info:tt)*
     }) => {
-        create_mock!(@create_mock_struct ($mock_name, ($($method_info)*)) -> ());
+        create_mock!(@impl [] [$trait_name] $mock_name [] ($self_) [] { $($method_info)* });
+    };
+    (impl < $($tail:tt)*) => {
+        create_mock!(@impl_generics [] $($tail)*);
+    };
+    (impl $($tail:tt)*) => {
+        create_mock!(@impl_trait [] [] $($tail)*);
+    };
 
-        impl $trait_name for $mock_name {
-            create_mock!(@create_stub_methods ($self_) $($method_info)*);
+    // Namespace the keys of each trait's methods with the trait's name, like
+    // "Reader::read", so that methods of different traits can't collide.
+    (@traits $mock_name:ident $self_:tt $done:tt [$trait_name:ident { $($method_info:tt)* } $($rest:tt)*]) => {
+        create_mock!(@namespace ($mock_name $self_ $done [$($rest)*] $trait_name) [] $($method_info)*);
+    };
+    (@namespace ($mock_name:ident $self_:tt $done:tt $rest:tt $trait_name:ident) [$($result:tt)*] $expect_name:ident($key:expr): $($tail:tt)*) => {
+        create_mock!(@take_type (@namespace_sig ($mock_name $self_ $done $rest $trait_name)) [$($result)* $expect_name(concat!(stringify!($trait_name), "::", $key)):] $($tail)*);
+    };
+    (@namespace_sig $state:tt [$($result:tt)*] $($tail:tt)*) => {
+        create_mock!(@namespace $state [$($result)* ;] $($tail)*);
+    };
+    (@namespace ($mock_name:ident $self_:tt [$($done:tt)*] $rest:tt $trait_name:ident) [$($result:tt)*]) => {
+        create_mock!(@traits $mock_name $self_ [$($done)* $trait_name { $($result)* }] $rest);
+    };
+    (@traits $mock_name:ident ($self_:ident) [$($trait_name:ident { $($method_info:tt)* })*] []) => {
+        create_mock!(@create_mock_struct ([$mock_name], [], ($($($method_info)*)*)) -> ());
+
+        $(
+            impl $trait_name for $mock_name {
+                create_mock!(@create_stub_methods ($self_, []) $($method_info)*);
+            }
+        )*
+    };
+
+    // Collect the generic parameters of the impl, up to the trait's name.
+    (@impl_generics [$($generics:tt)*] > $trait_name:ident $($tail:tt)*) => {
+        create_mock!(@impl_trait [$($generics)*] [$trait_name] $($tail)*);
+    };
+    (@impl_generics [$($generics:tt)*] >> $trait_name:ident $($tail:tt)*) => {
+        create_mock!(@impl_trait [$($generics)* >] [$trait_name] $($tail)*);
+    };
+    (@impl_generics [$($generics:tt)*] $token:tt $($tail:tt)*) => {
+        create_mock!(@impl_generics [$($generics)* $token] $($tail)*);
+    };
+
+    // Collect the trait's path and arguments, up to `for`.
+    (@impl_trait $generics:tt $trait_path:tt for $mock_name:ident < $($param:tt),* > ($self_:ident) $($tail:tt)*) => {
+        create_mock!(@impl_where $generics $trait_path $mock_name [$($param),*] ($self_) [] $($tail)*);
+    };
+    (@impl_trait $generics:tt $trait_path:tt for $mock_name:ident ($self_:ident) $($tail:tt)*) => {
+        create_mock!(@impl_where $generics $trait_path $mock_name [] ($self_) [] $($tail)*);
+    };
+    (@impl_trait $generics:tt [$($trait_path:tt)*] $token:tt $($tail:tt)*) => {
+        create_mock!(@impl_trait $generics [$($trait_path)* $token] $($tail)*);
+    };
+
+    // Collect the where clause, if any, up to the methods.
+    (@impl_where $generics:tt $trait_path:tt $mock_name:ident $params:tt $self_:tt [] where $($tail:tt)*) => {
+        create_mock!(@impl_where $generics $trait_path $mock_name $params $self_ [] $($tail)*);
+    };
+    (@impl_where $generics:tt $trait_path:tt $mock_name:ident $params:tt $self_:tt $where_clause:tt { $($method_info:tt)* }) => {
+        create_mock!(@impl $generics $trait_path $mock_name $params $self_ $where_clause { $($method_info)* });
+    };
+    (@impl_where $generics:tt $trait_path:tt $mock_name:ident $params:tt $self_:tt [$($where_clause:tt)*] $token:tt $($tail:tt)*) => {
+        create_mock!(@impl_where $generics $trait_path $mock_name $params $self_ [$($where_clause)* $token] $($tail)*);
+    };
+
+    // Sort the mock's generic parameters into lifetimes and types, since only
+    // types need to be `'static`.
+    (@impl $generics:tt $trait_path:tt $mock_name:ident [$($param:tt),*] $self_:tt $where_clause:tt $method_info:tt) => {
+        create_mock!(@impl_params ($generics $trait_path $mock_name [$($param),*] $self_ $where_clause $method_info) [] [] ($($param)*));
+    };
+    (@impl_params $info:tt [$($lifetime:tt)*] [$($type_param:tt)*] ($next:lifetime $($rest:tt)*)) => {
+        create_mock!(@impl_params $info [$($lifetime)* $next] [$($type_param)*] ($($rest)*));
+    };
+    (@impl_params $info:tt [$($lifetime:tt)*] [$($type_param:tt)*] ($next:ident $($rest:tt)*)) => {
+        create_mock!(@impl_params $info [$($lifetime)*] [$($type_param)* $next] ($($rest)*));
+    };
+    (@impl_params
+        (
+            [$($generics:tt)*] [$($trait_path:tt)*] $mock_name:ident [$($param:tt),*] ($self_:ident) [$($where_clause:tt)*] {
+                $($method_info:tt)*
+            }
+        )
+        [$($lifetime:tt)*] [$($type_param:ident)*] ()
+    ) => {
+        create_mock!(@impl_items (
+            [$($generics)*] [$($trait_path)*] $mock_name [$($param),*] [$($type_param)*] ($self_) [$($where_clause)*]
+        ) [] [] $($method_info)*);
+    };
+
+    // Collect the associated types and constants bound at the start of the
+    // body. If there are associated types, the mock's methods may use them,
+    // and only objects with the same types can be delegated to.
+    (@impl_items $info:tt [$($items:tt)*] [$($bindings:tt)*] type $name:ident = $value:ty; $($tail:tt)*) => {
+        create_mock!(@impl_items $info [$($items)* type $name = $value;] [$($bindings)* $name = $value,] $($tail)*);
+    };
+    (@impl_items $info:tt [$($items:tt)*] $bindings:tt const $name:ident: $kind:ty = $value:expr; $($tail:tt)*) => {
+        create_mock!(@impl_items $info [$($items)* const $name: $kind = $value;] $bindings $($tail)*);
+    };
+    (@impl_items ($generics:tt [$($trait_path:tt)*] $($info:tt)*) $items:tt [] $($method_info:tt)*) => {
+        create_mock!(@impl_generate ($generics [$($trait_path)*] $($info)*) $items [] [$($trait_path)*] $($method_info)*);
+    };
+    (@impl_items ($generics:tt [$($trait_path:tt)*] $($info:tt)*) $items:tt $bindings:tt $($method_info:tt)*) => {
+        create_mock!(@delegate_bound (($generics [$($trait_path)*] $($info)*) $items [$($trait_path)*]) { $($method_info)* } $bindings [] ($($trait_path)*));
+    };
+
+    // Add the associated type bindings to the trait's arguments.
+    (@delegate_bound ($($state:tt)*) { $($method_info:tt)* } [$($bindings:tt)*] [$($path:tt)*] ()) => {
+        create_mock!(@impl_generate $($state)* [$($path)* <$($bindings)*>] $($method_info)*);
+    };
+    (@delegate_bound $state:tt $methods:tt $bindings:tt [$($path:tt)*] (< $($rest:tt)*)) => {
+        create_mock!(@delegate_bound_args $state $methods $bindings [$($path)* <] ($($rest)*));
+    };
+    (@delegate_bound $state:tt $methods:tt $bindings:tt [$($path:tt)*] ($token:tt $($rest:tt)*)) => {
+        create_mock!(@delegate_bound $state $methods $bindings [$($path)* $token] ($($rest)*));
+    };
+    (@delegate_bound_args ($($state:tt)*) { $($method_info:tt)* } [$($bindings:tt)*] [$($path:tt)*] (>)) => {
+        create_mock!(@impl_generate $($state)* [$($path)*, $($bindings)* >] $($method_info)*);
+    };
+    (@delegate_bound_args ($($state:tt)*) { $($method_info:tt)* } [$($bindings:tt)*] [$($path:tt)*] (>>)) => {
+        create_mock!(@impl_generate $($state)* [$($path)* >, $($bindings)* >] $($method_info)*);
+    };
+    (@delegate_bound_args $state:tt $methods:tt $bindings:tt [$($path:tt)*] ($token:tt $($rest:tt)*)) => {
+        create_mock!(@delegate_bound_args $state $methods $bindings [$($path)* $token] ($($rest)*));
+    };
+
+    (@impl_generate
+        (
+            [$($generics:tt)*] [$($trait_path:tt)*] $mock_name:ident [$($param:tt),*] [$($type_param:ident)*] ($self_:ident) [$($where_clause:tt)*]
+        )
+        [$($items:tt)*] $qualify:tt [$($delegate_bound:tt)*] $($method_info:tt)*
+    ) => {
+        create_mock!(@create_mock_struct ([$mock_name<$($param),*>], $qualify, ($($method_info)*)) -> ());
+
+        impl<$($generics)*> $($trait_path)* for $mock_name<$($param),*> where
+            $($type_param: 'static,)*
+            $($where_clause)*
+        {
+            $($items)*
+
+            create_mock!(@create_stub_methods ($self_, []) $($method_info)*);
+        }
+
+        #[allow(dead_code)]
+        impl<$($generics)*> $mock_name<$($param),*> where
+            $($type_param: 'static,)*
+            $($where_clause)*
+        {
+            /// Forward calls to `inner` whenever no expectation specifies a
+            /// return value with `returning()`. Expectations are still checked.
+            ///
+            /// Only methods taking `&self` or `&mut self` are forwarded. Generic
+            /// methods, `async` methods and methods returning futures, methods
+            /// taking `self` by value or through a smart pointer, methods with
+            /// params borrowed for a named lifetime other than `'static`, and
+            /// functions without `self` aren't, so they still need
+            /// `returning()` if they return a value.
+            pub fn delegate_to<__Inner>(&mut $self_, inner: __Inner) -> &mut Self where
+                __Inner: $($delegate_bound)* + $crate::MaybeSend + 'static
+            {
+                let inner = ::std::sync::Arc::new(::std::sync::Mutex::new(inner));
+                create_mock!(@create_delegates ($self_, inner, $qualify) $($method_info)*);
+                $self_
+            }
         }
     };
 }
@@ -1,3 +1,5 @@
+use simulacrum_shared::MaybeSend;
+
 pub mod result;
 pub mod stock;
 
@@ -7,7 +9,7 @@ pub use self::result::*;
 ///
 /// All `Constraint`s added to an `Expectation` must all pass in order for the
 /// `Expectation` to pass.
-pub trait Constraint<I> {
+pub trait Constraint<I>: MaybeSend {
     /// This constraint has been called with the given parameters. Update the
     ///
     /// Constraint state so that when `verify()` is called, it will return the
@@ -15,6 +17,26 @@ pub trait Constraint<I> {
     #[allow(unused_variables)]
     fn handle_call(&mut self, params: &I) { }
 
+    /// Return `false` if a call with the given parameters should not be counted
+    /// against this Constraint at all.
+    ///
+    /// This is used to pick which `Expectation` a call belongs to when several
+    /// have been set for the same method in one Era.
+    #[allow(unused_variables)]
+    fn accepts(&mut self, params: &I) -> bool { true }
+
+    /// Same as `handle_call()`, for a call that `accepts()` has just returned
+    /// `true` for. Constraints that check the parameters don't need to again.
+    fn handle_accepted_call(&mut self, params: &I) {
+        self.handle_call(params)
+    }
+
+    /// Return `true` if another call would make this Constraint fail.
+    ///
+    /// Once every Era is complete, strict mode reports calls to exhausted
+    /// `Expectation`s as unexpected.
+    fn is_exhausted(&self) -> bool { false }
+
     /// At the end of the test, see if the Constraint passed or failed.
     fn verify(&self) -> ConstraintResult;
 }
@@ -6,32 +6,48 @@ pub type ConstraintResult = Result<(), ConstraintError>;
 #[derive(Clone, Debug, PartialEq)]
 pub enum ConstraintError {
     AlwaysFail,
+    CalledOutOfSequence(String, String), // Method called, Method that should have been called first
+    CalledOutsideRange(i64, Option<i64>, i64), // Minimum, Maximum (if any), Received
     CalledTooFewTimes(i64),
     CalledTooManyTimes(i64),
-    CallNotExpected,
+    CallNotExpected(String), // Received Message
+    CallTypesNotExpected(String, String), // Expected Types, Received Types
     Custom(String), // For custom constraints from users
     MismatchedParams(String, String), // Expected Message, Received Message
 }
 
 impl fmt::Display for ConstraintError {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        match self {
-            &ConstraintError::AlwaysFail => {
+        match *self {
+            ConstraintError::AlwaysFail => {
                 write!(f, "Expectation will always fail.")
             },
-            &ConstraintError::CalledTooFewTimes(times) => {
+            ConstraintError::CalledOutOfSequence(ref called, ref expected_first) => {
+                write!(f, "Called out of sequence: `{}` was called before `{}`.", called, expected_first)
+            },
+            ConstraintError::CalledOutsideRange(min, max, received) => {
+                match max {
+                    None => write!(f, "Called {} times, expected at least {}.", received, min),
+                    Some(max) if min == 0 => write!(f, "Called {} times, expected at most {}.", received, max),
+                    Some(max) => write!(f, "Called {} times, expected between {} and {}.", received, min, max)
+                }
+            },
+            ConstraintError::CalledTooFewTimes(times) => {
                 write!(f, "Called {} times fewer than expected.", times)
             },
-            &ConstraintError::CalledTooManyTimes(times) => {
+            ConstraintError::CalledTooManyTimes(times) => {
                 write!(f, "Called {} times more than expected.", times)
             },
-            &ConstraintError::CallNotExpected => {
-                write!(f, "Called when not expected.")
+            ConstraintError::CallNotExpected(ref received_msg) => {
+                write!(f, "Called when not expected:\n  Received: {}", received_msg)
+            },
+            ConstraintError::CallTypesNotExpected(ref expected_types, ref received_types) => {
+                write!(f, "Called with unexpected types:\n  Expected: {}\n  Received: {}", expected_types, received_types)
             },
-            &ConstraintError::Custom(ref msg) => {
+            ConstraintError::Custom(ref msg) => {
                 write!(f, "{}", msg)
             },
-            &ConstraintError::MismatchedParams(ref expected_msg, ref received_msg) => {
+            ConstraintError::MismatchedParams(ref expected_msg, ref received_msg) => {
                 write!(f, "Called with unexpected parameters:\n  Expected: {}\n  Received: {}", expected_msg, received_msg)
             },
         }
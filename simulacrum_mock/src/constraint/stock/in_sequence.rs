@@ -0,0 +1,84 @@
+use constraint::{Constraint, ConstraintError, ConstraintResult};
+use sequence::Sequence;
+
+/// A method must be called after every method added to a `Sequence` before it.
+pub struct InSequence {
+    sequence: Sequence,
+    position: usize,
+    /// Set to the description of the step that should have been called first,
+    /// if this method was called out of order.
+    called_before: Option<String>
+}
+
+impl InSequence {
+    /// Add a method to the end of the given `Sequence`. `description` is used
+    /// in error messages, and should include the mock object's name.
+    pub fn new<S: ToString>(sequence: &Sequence, description: S) -> Self {
+        let position = sequence.add(description.to_string());
+        InSequence {
+            sequence: sequence.clone(),
+            position,
+            called_before: None
+        }
+    }
+}
+
+impl<I> Constraint<I> for InSequence {
+    fn handle_call(&mut self, _params: &I) {
+        if self.called_before.is_none() {
+            self.called_before = self.sequence.call(self.position).err();
+        }
+    }
+
+    fn verify(&self) -> ConstraintResult {
+        match self.called_before {
+            Some(ref expected_first) => {
+                let called = self.sequence.description(self.position);
+                Err(ConstraintError::CalledOutOfSequence(called, expected_first.clone()))
+            },
+            None => Ok(())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_new() {
+        let s = Sequence::new();
+        let c = InSequence::new(&s, "A::first");
+
+        let r = <Constraint<()>>::verify(&c);
+
+        assert!(r.is_ok(), "Constraint should pass after being created");
+    }
+
+    #[test]
+    fn test_handle_call_in_order() {
+        let s = Sequence::new();
+        let mut first = InSequence::new(&s, "A::first");
+        let mut second = InSequence::new(&s, "B::second");
+
+        first.handle_call(&());
+        second.handle_call(&());
+
+        assert!(<Constraint<()>>::verify(&first).is_ok(), "First constraint should pass");
+        assert!(<Constraint<()>>::verify(&second).is_ok(), "Second constraint should pass");
+    }
+
+    #[test]
+    fn test_handle_call_out_of_order() {
+        let s = Sequence::new();
+        let mut first = InSequence::new(&s, "A::first");
+        let mut second = InSequence::new(&s, "B::second");
+
+        second.handle_call(&());
+        first.handle_call(&());
+
+        assert!(<Constraint<()>>::verify(&first).is_ok(), "First constraint should pass");
+        let r = <Constraint<()>>::verify(&second);
+        assert_eq!(r.unwrap_err(), ConstraintError::CalledOutOfSequence("B::second".to_owned(), "A::first".to_owned()), "Constraint should return the correct error");
+    }
+}
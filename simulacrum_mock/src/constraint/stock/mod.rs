@@ -1,5 +1,6 @@
 //! Types that impl `Constraint` that are included with Simulacrum.
 
 pub mod always;
+pub mod in_sequence;
 pub mod params;
 pub mod times;
\ No newline at end of file
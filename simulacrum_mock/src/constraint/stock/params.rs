@@ -35,6 +35,13 @@ impl<I> Constraint<I> for Params<I> {
         }
     }
 
+    fn accepts(&mut self, params: &I) -> bool {
+        self.validator.validate(params)
+    }
+
+    // The validator already passed these parameters in `accepts()`.
+    fn handle_accepted_call(&mut self, _params: &I) { }
+
     fn verify(&self) -> ConstraintResult {
         if self.is_valid {
             Ok(())
@@ -95,6 +102,18 @@ mod tests {
         assert!(r.is_err(), "Constraint should fail");
     }
 
+    #[test]
+    fn test_accepts() {
+        // Validator closure approves input over 5
+        let mut c = Params::new(passes(|arg| *arg > 5));
+
+        assert!(c.accepts(&10), "Constraint should accept valid params");
+        assert!(!c.accepts(&3), "Constraint should not accept invalid params");
+
+        let r = <Constraint<i32>>::verify(&c);
+        assert!(r.is_ok(), "Checking params should not affect the result");
+    }
+
     #[test]
     fn test_handle_call_bad_then_good() {
         // Validator closure approves input over 5
@@ -1,24 +1,70 @@
 use constraint::{Constraint, ConstraintError, ConstraintResult};
 
 /// A method must be called a certain number of times
-pub struct Times(i64);
+pub struct Times {
+    calls: i64,
+    min: i64,
+    max: Option<i64>
+}
 
 impl Times {
+    /// The method must be called exactly `expected_calls` times.
     pub fn new(expected_calls: i64) -> Self {
-        Times(expected_calls)
+        Self::between(expected_calls, expected_calls)
+    }
+
+    /// The method must be called `min` or more times.
+    pub fn at_least(min: i64) -> Self {
+        Times {
+            calls: 0,
+            min,
+            max: None
+        }
+    }
+
+    /// The method must be called `max` or fewer times.
+    pub fn at_most(max: i64) -> Self {
+        Self::between(0, max)
+    }
+
+    /// The method must be called between `min` and `max` times, inclusive.
+    pub fn between(min: i64, max: i64) -> Self {
+        Times {
+            calls: 0,
+            min,
+            max: Some(max)
+        }
     }
 }
 
 impl<I> Constraint<I> for Times {
     fn handle_call(&mut self, _params: &I) {
-        self.0 -= 1;
+        self.calls += 1;
+    }
+
+    fn is_exhausted(&self) -> bool {
+        self.max.is_some_and(|max| self.calls >= max)
     }
 
     fn verify(&self) -> ConstraintResult {
-        match self.0 {
-            x if x < 0 => Err(ConstraintError::CalledTooManyTimes(x.abs())),
-            x if x > 0 => Err(ConstraintError::CalledTooFewTimes(x)),
-            _ => Ok(())
+        match self.max {
+            // Exact counts report how far off the count was
+            Some(max) if max == self.min => {
+                match self.calls - max {
+                    x if x > 0 => Err(ConstraintError::CalledTooManyTimes(x)),
+                    x if x < 0 => Err(ConstraintError::CalledTooFewTimes(x.abs())),
+                    _ => Ok(())
+                }
+            },
+            max => {
+                let too_few = self.calls < self.min;
+                let too_many = max.is_some_and(|max| self.calls > max);
+                if too_few || too_many {
+                    Err(ConstraintError::CalledOutsideRange(self.min, max, self.calls))
+                } else {
+                    Ok(())
+                }
+            }
         }
     }
 }
@@ -31,7 +77,7 @@ mod tests {
     fn test_pass() {
         let c = Times::new(0);
 
-        let r = <Constraint<()>>::verify(&c);
+        let r = <dyn Constraint<()>>::verify(&c);
 
         assert!(r.is_ok());
     }
@@ -40,7 +86,7 @@ mod tests {
     fn test_fail_called_fewer() {
         let c = Times::new(1);
 
-        let r = <Constraint<()>>::verify(&c);
+        let r = <dyn Constraint<()>>::verify(&c);
 
         assert!(r.is_err(), "Constraint should fail");
         assert_eq!(r.unwrap_err(), ConstraintError::CalledTooFewTimes(1), "Constraint should return the correct error");
@@ -50,7 +96,7 @@ mod tests {
     fn test_fail_called_more() {
         let c = Times::new(-1);
 
-        let r = <Constraint<()>>::verify(&c);
+        let r = <dyn Constraint<()>>::verify(&c);
 
         assert!(r.is_err(), "Constraint should fail");
         assert_eq!(r.unwrap_err(), ConstraintError::CalledTooManyTimes(1), "Constraint should return the correct error");
@@ -63,8 +109,64 @@ mod tests {
         // Called twice
         c.handle_call(&());
         c.handle_call(&());
-        let r = <Constraint<()>>::verify(&c);
+        let r = <dyn Constraint<()>>::verify(&c);
 
         assert!(r.is_ok());
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_at_least() {
+        let mut c = Times::at_least(1);
+
+        let r = <dyn Constraint<()>>::verify(&c);
+        assert_eq!(r.unwrap_err(), ConstraintError::CalledOutsideRange(1, None, 0), "Constraint should return the correct error");
+
+        c.handle_call(&());
+        c.handle_call(&());
+        let r = <dyn Constraint<()>>::verify(&c);
+        assert!(r.is_ok(), "Constraint should pass once the lower bound is met");
+    }
+
+    #[test]
+    fn test_at_most() {
+        let mut c = Times::at_most(1);
+
+        let r = <dyn Constraint<()>>::verify(&c);
+        assert!(r.is_ok(), "Constraint should pass with no calls");
+
+        c.handle_call(&());
+        c.handle_call(&());
+        let r = <dyn Constraint<()>>::verify(&c);
+        assert_eq!(r.unwrap_err(), ConstraintError::CalledOutsideRange(0, Some(1), 2), "Constraint should return the correct error");
+    }
+
+    #[test]
+    fn test_between() {
+        let mut c = Times::between(1, 2);
+
+        let r = <dyn Constraint<()>>::verify(&c);
+        assert_eq!(r.unwrap_err(), ConstraintError::CalledOutsideRange(1, Some(2), 0), "Constraint should return the correct error");
+
+        c.handle_call(&());
+        let r = <dyn Constraint<()>>::verify(&c);
+        assert!(r.is_ok(), "Constraint should pass within the range");
+
+        c.handle_call(&());
+        c.handle_call(&());
+        let r = <dyn Constraint<()>>::verify(&c);
+        assert_eq!(r.unwrap_err(), ConstraintError::CalledOutsideRange(1, Some(2), 3), "Constraint should return the correct error");
+    }
+
+    #[test]
+    fn test_exhausted() {
+        let mut c = Times::between(1, 2);
+        let unbounded = Times::at_least(1);
+
+        c.handle_call(&());
+        assert!(!<dyn Constraint<()>>::is_exhausted(&c), "Constraint should take another call");
+
+        c.handle_call(&());
+        assert!(<dyn Constraint<()>>::is_exhausted(&c), "Constraint should take no more calls");
+        assert!(!<dyn Constraint<()>>::is_exhausted(&unbounded), "Constraint without a maximum should never be exhausted");
+    }
+}
@@ -1,13 +1,80 @@
+use simulacrum_shared::MaybeSend;
+
 use std::any::Any;
 use std::cell::RefCell;
+use std::mem;
 use std::ops::{Deref, DerefMut};
 
 use super::MethodName;
+use super::future::MockFuture;
+use super::method::TypeSig;
 
 pub mod result;
 
 pub use super::constraint::Constraint;
-pub use self::result::{ExpectationError, ExpectationResult};
+pub use self::result::{ExpectationError, ExpectationResult, VerificationReport};
+
+// Closures stored by an `Expectation`. These let the closures be `Send` only
+// when `MaybeSend` requires it.
+trait ModificationFn<I>: MaybeSend {
+    fn modify(&mut self, params: &mut I);
+}
+
+impl<I, F> ModificationFn<I> for F where
+    F: FnMut(&mut I) + MaybeSend
+{
+    fn modify(&mut self, params: &mut I) {
+        self(params)
+    }
+}
+
+trait ReturnFn<I, O>: MaybeSend {
+    fn return_value(&mut self, params: I) -> O;
+}
+
+impl<I, O, F> ReturnFn<I, O> for F where
+    F: FnMut(I) -> O + MaybeSend
+{
+    fn return_value(&mut self, params: I) -> O {
+        self(params)
+    }
+}
+
+/// A closure that handles calls to a method whenever no `Expectation` has a
+/// closure of its own to produce return values, e.g. by forwarding them to a
+/// real implementation.
+pub(crate) struct Delegate<I, O>(Box<dyn ReturnFn<I, O>>);
+
+impl<I, O> Delegate<I, O> {
+    pub fn new<F>(delegate_behavior: F) -> Self where
+        F: 'static + FnMut(I) -> O + MaybeSend
+    {
+        Delegate(Box::new(delegate_behavior))
+    }
+
+    pub fn call(&mut self, params: I) -> O {
+        self.0.return_value(params)
+    }
+}
+
+pub(crate) trait DelegateT: MaybeSend {
+    fn as_any(&mut self) -> &mut dyn Any;
+
+    fn types(&self) -> TypeSig;
+}
+
+impl<I, O> DelegateT for Delegate<I, O> where
+    I: 'static,
+    O: 'static
+{
+    fn as_any(&mut self) -> &mut dyn Any {
+        self
+    }
+
+    fn types(&self) -> TypeSig {
+        TypeSig::of::<I, O>()
+    }
+}
 
 /// An expectation that a method must be called. Also includes an optional
 /// closure to produce return values, if necessary.
@@ -16,8 +83,13 @@ pub struct Expectation<I, O> where
 {
     name: MethodName,
     constraints: Vec<Box<Constraint<I>>>,
-    modification_fn: Option<Box<FnMut(&mut I)>>,
-    return_fn: Option<Box<FnMut(I) -> O>>
+    // Set when `accepts()` picked this Expectation for the call about to be
+    // handled, so that its parameters aren't checked twice.
+    accepted: bool,
+    modification_fn: Option<Box<dyn ModificationFn<I>>>,
+    return_fn: Option<Box<dyn ReturnFn<I, O>>>,
+    // Produces futures for `async` methods, instead of wrapping `return_fn`'s values.
+    future_fn: Option<Box<dyn ReturnFn<I, MockFuture<O>>>>
 }
 
 impl<I, O> Expectation<I, O> where
@@ -28,38 +100,73 @@ impl<I, O> Expectation<I, O> where
         Expectation {
             name: name.to_string(),
             constraints: Vec::new(),
+            accepted: false,
             modification_fn: None,
-            return_fn: None
+            return_fn: None,
+            future_fn: None
         }
     }
 
+    /// Returns `true` if every constraint on this expectation would accept a
+    /// call with the given parameters.
+    pub fn accepts(&mut self, params_cell: &RefCell<I>) -> bool {
+        let params = params_cell.borrow();
+        self.accepted = self.constraints
+            .iter_mut()
+            .all(|constraint| constraint.accepts(params.deref()));
+        self.accepted
+    }
+
     pub fn handle_call(&mut self, params_cell: &RefCell<I>) {
         self.constraints_handle_call(params_cell);
         self.run_modification_behavior(params_cell);
     }
 
     fn constraints_handle_call(&mut self, params_cell: &RefCell<I>) {
+        let accepted = mem::replace(&mut self.accepted, false);
         for constraint in self.constraints.iter_mut() {
             let params = params_cell.borrow();
-            constraint.handle_call(params.deref());
+            if accepted {
+                constraint.handle_accepted_call(params.deref());
+            } else {
+                constraint.handle_call(params.deref());
+            }
         }
     }
 
     fn run_modification_behavior(&mut self, params_cell: &RefCell<I>) {
         if self.modification_fn.is_some() {
             let mut params = params_cell.borrow_mut();
-            (self.modification_fn.as_mut().unwrap())(params.deref_mut())
+            self.modification_fn.as_mut().unwrap().modify(params.deref_mut())
         }
     }
 
+    /// Returns `true` if a closure to produce return values has been set.
+    pub fn has_return_behavior(&self) -> bool {
+        self.return_fn.is_some()
+    }
+
     pub fn return_value_for(&mut self, params_cell: RefCell<I>) -> O {
         if self.return_fn.is_some() {
-            (self.return_fn.as_mut().unwrap())(params_cell.into_inner())
+            self.return_fn.as_mut().unwrap().return_value(params_cell.into_inner())
         } else {
             panic!("No return closure specified for `{}`, which should return.", self.name);
         }
     }
 
+    /// Returns `true` if a closure to produce futures has been set.
+    pub fn has_future_behavior(&self) -> bool {
+        self.future_fn.is_some()
+    }
+
+    pub fn future_for(&mut self, params_cell: RefCell<I>) -> MockFuture<O> {
+        if let Some(ref mut future_fn) = self.future_fn {
+            future_fn.return_value(params_cell.into_inner())
+        } else {
+            panic!("No future closure specified for `{}`.", self.name);
+        }
+    }
+
     pub(crate) fn constrain<C>(&mut self, constraint: C) where
         C: Constraint<I> + 'static
     {
@@ -67,24 +174,34 @@ impl<I, O> Expectation<I, O> where
     }
 
     pub(crate) fn set_modification<F>(&mut self, modification_behavior: F) where
-        F: 'static + FnMut(&mut I)
+        F: 'static + FnMut(&mut I) + MaybeSend
     {
         self.modification_fn = Some(Box::new(modification_behavior));
     }
 
     pub(crate) fn set_return<F>(&mut self, return_behavior: F) where
-        F: 'static + FnMut(I) -> O
+        F: 'static + FnMut(I) -> O + MaybeSend
     {
         self.return_fn = Some(Box::new(return_behavior));
     }
+
+    pub(crate) fn set_future_return<F>(&mut self, future_behavior: F) where
+        F: 'static + FnMut(I) -> MockFuture<O> + MaybeSend
+    {
+        self.future_fn = Some(Box::new(future_behavior));
+    }
 }
 
-pub trait ExpectationT {
+pub trait ExpectationT: MaybeSend {
     fn as_any(&mut self) -> &mut Any;
 
     fn verify(&self) -> ExpectationResult;
 
+    fn is_exhausted(&self) -> bool;
+
     fn name(&self) -> &MethodName;
+
+    fn types(&self) -> TypeSig;
 }
 
 impl<I, O> ExpectationT for Expectation<I, O> where
@@ -107,9 +224,17 @@ impl<I, O> ExpectationT for Expectation<I, O> where
         Ok(())
     }
 
+    fn is_exhausted(&self) -> bool {
+        self.constraints.iter().any(|constraint| constraint.is_exhausted())
+    }
+
     fn name(&self) -> &MethodName {
         &self.name
     }
+
+    fn types(&self) -> TypeSig {
+        TypeSig::of::<I, O>()
+    }
 }
 
 #[cfg(test)]
@@ -117,7 +242,8 @@ mod tests {
     use super::*;
     use constraint::{ConstraintError, ConstraintMock};
     use constraint::stock::always::{AlwaysFail, AlwaysPass};
-    use std::{sync::Arc, cell::RefCell};
+    use constraint::stock::params::Params;
+    use std::{sync::{Arc, Mutex}, cell::RefCell};
 
     #[test]
     fn test_new() {
@@ -141,6 +267,15 @@ mod tests {
         // ConstraintMock verifies on Drop
     }
 
+    #[test]
+    fn test_accepts() {
+        let mut e: Expectation<i32, ()> = Expectation::new("foo");
+        e.constrain(Params::new(5));
+
+        assert!(e.accepts(&RefCell::new(5)), "Expectation should accept 5");
+        assert!(!e.accepts(&RefCell::new(6)), "Expectation should not accept 6");
+    }
+
     #[test]
     fn test_constrain() {
         let mut e: Expectation<(), ()> = Expectation::new("test");
@@ -167,15 +302,15 @@ mod tests {
 
         let mut e: Expectation<UniquelyOwned, ()> = Expectation::new("foo");
 
-        let dest: Arc<RefCell<Option<UniquelyOwned>>> =
-            Arc::new(RefCell::new(None));
+        let dest: Arc<Mutex<Option<UniquelyOwned>>> =
+            Arc::new(Mutex::new(None));
         let dest2 = dest.clone();
         e.set_return(move |x| {
-            dest2.replace(Some(x));
+            dest2.lock().unwrap().replace(x);
         });
         e.return_value_for(RefCell::new(UniquelyOwned(42)));
 
-        assert!(dest.borrow().is_some());
+        assert_eq!(dest.lock().unwrap().as_ref().map(|x| x.0), Some(42));
     }
 
     #[test]
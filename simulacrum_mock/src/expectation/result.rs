@@ -15,4 +15,17 @@ impl fmt::Display for ExpectationError {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         write!(f, "{}: {}", self.method_name, self.constraint_err)
     }
-}
\ No newline at end of file
+}
+
+/// Every `ExpectationError` found when verifying an `Expectations` object.
+#[derive(Clone, Debug, PartialEq)]
+pub struct VerificationReport {
+    pub errors: Vec<ExpectationError>
+}
+
+impl fmt::Display for VerificationReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let lines: Vec<String> = self.errors.iter().map(|e| e.to_string()).collect();
+        write!(f, "{}", lines.join("\n"))
+    }
+}
@@ -0,0 +1,158 @@
+//! Futures returned by mocked `async` methods, and a minimal executor for
+//! driving them in tests without a runtime.
+
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll, Wake, Waker};
+use std::thread::{self, Thread};
+
+/// A future returned by a mocked `async` method.
+///
+/// It is either ready straight away, or pending until the test resolves it
+/// with the matching `Resolver`.
+pub struct MockFuture<T>(State<T>);
+
+enum State<T> {
+    Ready(Option<T>),
+    Pending(Arc<Mutex<Slot<T>>>)
+}
+
+struct Slot<T> {
+    value: Option<T>,
+    waker: Option<Waker>
+}
+
+impl<T> MockFuture<T> {
+    /// A future that resolves to `value` the first time it is polled.
+    pub fn ready(value: T) -> Self {
+        MockFuture(State::Ready(Some(value)))
+    }
+
+    /// A future that stays pending until `resolve()` is called on the returned
+    /// `Resolver`.
+    pub fn pending() -> (Resolver<T>, Self) {
+        let slot = Arc::new(Mutex::new(Slot {
+            value: None,
+            waker: None
+        }));
+        (Resolver(slot.clone()), MockFuture(State::Pending(slot)))
+    }
+}
+
+impl<T> From<T> for MockFuture<T> {
+    fn from(value: T) -> Self {
+        MockFuture::ready(value)
+    }
+}
+
+// We never hand out pinned references to `T`, so moving it is fine.
+impl<T> Unpin for MockFuture<T> { }
+
+impl<T> Future for MockFuture<T> {
+    type Output = T;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context) -> Poll<T> {
+        match self.get_mut().0 {
+            State::Ready(ref mut value) => {
+                Poll::Ready(value.take().expect("MockFuture polled after it was complete."))
+            },
+            State::Pending(ref slot) => {
+                let mut slot = slot.lock().unwrap();
+                match slot.value.take() {
+                    Some(value) => Poll::Ready(value),
+                    None => {
+                        slot.waker = Some(cx.waker().clone());
+                        Poll::Pending
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Completes a pending `MockFuture`.
+pub struct Resolver<T>(Arc<Mutex<Slot<T>>>);
+
+impl<T> Resolver<T> {
+    /// Resolve the matching future to `value`, waking whatever is waiting on it.
+    pub fn resolve(self, value: T) {
+        let waker = {
+            let mut slot = self.0.lock().unwrap();
+            slot.value = Some(value);
+            slot.waker.take()
+        };
+        if let Some(waker) = waker {
+            waker.wake();
+        }
+    }
+}
+
+struct ThreadWaker(Thread);
+
+impl Wake for ThreadWaker {
+    fn wake(self: Arc<Self>) {
+        self.0.unpark();
+    }
+}
+
+fn current_thread_waker() -> Waker {
+    Waker::from(Arc::new(ThreadWaker(thread::current())))
+}
+
+/// Poll a future once, returning whether it is complete.
+pub fn poll_once<F>(future: &mut F) -> Poll<F::Output> where
+    F: Future + Unpin
+{
+    let waker = current_thread_waker();
+    let mut cx = Context::from_waker(&waker);
+    Pin::new(future).poll(&mut cx)
+}
+
+/// Run a future to completion on the current thread, parking it while the
+/// future is pending.
+///
+/// Futures that are resolved from the same thread must be resolved before
+/// this is called, or it will never return.
+pub fn block_on<F>(future: F) -> F::Output where
+    F: Future
+{
+    let waker = current_thread_waker();
+    let mut cx = Context::from_waker(&waker);
+    let mut future = Box::pin(future);
+    loop {
+        if let Poll::Ready(output) = future.as_mut().poll(&mut cx) {
+            return output;
+        }
+        thread::park();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ready() {
+        assert_eq!(block_on(MockFuture::ready(5)), 5);
+    }
+
+    #[test]
+    fn test_pending() {
+        let (resolver, mut future) = MockFuture::pending();
+        assert!(poll_once(&mut future).is_pending());
+        assert!(poll_once(&mut future).is_pending());
+
+        resolver.resolve("done");
+        assert_eq!(poll_once(&mut future), Poll::Ready("done"));
+    }
+
+    #[test]
+    fn test_resolve_from_other_thread() {
+        let (resolver, future) = MockFuture::pending();
+        let handle = thread::spawn(move || resolver.resolve(8));
+
+        assert_eq!(block_on(future), 8);
+        handle.join().unwrap();
+    }
+}
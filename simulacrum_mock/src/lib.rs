@@ -9,13 +9,21 @@ extern crate simulacrum_user;
 
 pub mod constraint;
 pub mod expectation;
+pub mod future;
 pub mod method;
 pub mod mock;
+pub mod record;
+pub mod sequence;
 mod store;
 
 pub type MethodName = String;
 
 pub use handlebox::Handle as ExpectationId;
+pub use simulacrum_shared::{Borrowed, BorrowedMut, MaybeSend, Scope};
 
+pub use self::expectation::VerificationReport;
+pub use self::future::{block_on, poll_once, MockFuture, Resolver};
 pub use self::mock::Expectations;
-pub use self::method::Method;
\ No newline at end of file
+pub use self::method::{AsMethodKey, Method, MethodKey, MockKey};
+pub use self::record::CallRecord;
+pub use self::sequence::Sequence;
\ No newline at end of file
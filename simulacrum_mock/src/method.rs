@@ -1,13 +1,19 @@
 //! This is the API that you'll call in your tests when using your Mock objects.
 
-use simulacrum_shared::Validator;
+use simulacrum_shared::{MaybeSend, Validator};
 
+use std::any::{self, TypeId};
+use std::fmt;
 use std::marker::PhantomData;
+use std::ops::RangeInclusive;
 
 use super::{ExpectationId, MethodName};
 use super::expectation::Expectation;
+use super::future::{MockFuture, Resolver};
+use super::constraint::stock::in_sequence::InSequence;
 use super::constraint::stock::times::Times;
 use super::constraint::stock::params::Params;
+use super::sequence::Sequence;
 use super::store::ExpectationStore;
 
 // I is a tuple of args for this method excluding self.
@@ -26,6 +32,266 @@ impl<I, O> MethodTypes<I, O> {
     }
 }
 
+/// A typed key for a method, so that the compiler can check that expectations
+/// and calls agree on the method's parameter and return types.
+///
+/// Declare one per method, e.g.
+/// `const GOOP: MethodKey<bool, u32> = MethodKey::new("goop");`
+pub struct MethodKey<I, O> {
+    name: &'static str,
+    generic: bool,
+    format: Option<fn(&I) -> String>,
+    _types: PhantomData<fn(I) -> O>
+}
+
+impl<I, O> MethodKey<I, O> {
+    pub const fn new(name: &'static str) -> Self {
+        MethodKey {
+            name,
+            generic: false,
+            format: None,
+            _types: PhantomData
+        }
+    }
+
+    /// A key for one instantiation of a generic method, like `put::<T>()`.
+    ///
+    /// Expectations for a generic method are kept apart by their types, so the
+    /// same name can be used with a different `I` and `O` for each `T`.
+    pub const fn generic(name: &'static str) -> Self {
+        MethodKey {
+            name,
+            generic: true,
+            format: None,
+            _types: PhantomData
+        }
+    }
+
+    /// Print the parameters of calls with `Debug`, in the call log and in
+    /// errors about unexpected calls. Otherwise they're shown as `<unknown>`.
+    pub const fn debug(self) -> Self where
+        I: fmt::Debug
+    {
+        MethodKey {
+            format: Some(format_debug::<I>),
+            ..self
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+}
+
+impl<I, O> Clone for MethodKey<I, O> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<I, O> Copy for MethodKey<I, O> { }
+
+impl<I, O> fmt::Debug for MethodKey<I, O> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "MethodKey({:?})", self.name)
+    }
+}
+
+/// Implemented by mock objects generated with the macros for each of their
+/// methods, so that a `MockKey` only fits the method's parameters `I` and
+/// return type `O`.
+///
+/// `ID` is the `method_id()` of the method's name.
+pub trait MockMethod<I, O, const ID: u64> {
+    const NAME: &'static str;
+
+    /// Prints the parameters of calls, if they implement `Debug`.
+    fn params_formatter() -> Option<fn(&I) -> String> {
+        None
+    }
+}
+
+/// The typed key of one of the methods of the mock object `M`, which the
+/// macros generate for each method.
+pub struct MockKey<M, const ID: u64>(PhantomData<fn() -> M>);
+
+impl<M, const ID: u64> MockKey<M, ID> {
+    pub const fn new() -> Self {
+        MockKey(PhantomData)
+    }
+}
+
+impl<M, const ID: u64> Clone for MockKey<M, ID> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<M, const ID: u64> Copy for MockKey<M, ID> { }
+
+impl<M, const ID: u64> Default for MockKey<M, ID> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[doc(hidden)]
+pub fn format_debug<I: fmt::Debug>(params: &I) -> String {
+    format!("{:?}", params)
+}
+
+// Lets the macros pick `format_debug()` for parameters that implement `Debug`
+// without requiring it, as `(&DebugParams::<I>::new()).formatter()` with both
+// of the traits below in scope. The `FormatDebug` impl is found first, but
+// only applies when `I: Debug`.
+#[doc(hidden)]
+pub struct DebugParams<I>(PhantomData<fn(&I)>);
+
+impl<I> DebugParams<I> {
+    pub fn new() -> Self {
+        DebugParams(PhantomData)
+    }
+}
+
+impl<I> Default for DebugParams<I> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[doc(hidden)]
+pub trait FormatDebug<I> {
+    fn formatter(&self) -> Option<fn(&I) -> String>;
+}
+
+impl<I: fmt::Debug> FormatDebug<I> for DebugParams<I> {
+    fn formatter(&self) -> Option<fn(&I) -> String> {
+        Some(format_debug::<I>)
+    }
+}
+
+#[doc(hidden)]
+pub trait FormatUnknown<I> {
+    fn formatter(&self) -> Option<fn(&I) -> String>;
+}
+
+impl<I> FormatUnknown<I> for &DebugParams<I> {
+    fn formatter(&self) -> Option<fn(&I) -> String> {
+        None
+    }
+}
+
+/// Hash a method's name into the `ID` of its `MockMethod` impl.
+#[doc(hidden)]
+pub const fn method_id(name: &str) -> u64 {
+    // FNV-1a
+    let bytes = name.as_bytes();
+    let mut hash: u64 = 0xcbf29ce484222325;
+    let mut i = 0;
+    while i < bytes.len() {
+        hash ^= bytes[i] as u64;
+        hash = hash.wrapping_mul(0x100000001b3);
+        i += 1;
+    }
+    hash
+}
+
+/// Anything that identifies a method with parameters `I` and return type `O`.
+///
+/// A `MethodKey<I, O>` only identifies methods with those types. Plain method
+/// names can be used for any types, which must then be given explicitly.
+pub trait AsMethodKey<I, O> {
+    fn method_name(&self) -> &str;
+
+    /// Whether this is a generic method, whose types vary between calls.
+    fn is_generic(&self) -> bool {
+        false
+    }
+
+    /// Prints the parameters of calls, which are shown as `<unknown>` without it.
+    fn params_formatter(&self) -> Option<fn(&I) -> String> {
+        None
+    }
+}
+
+impl<I, O> AsMethodKey<I, O> for MethodKey<I, O> {
+    fn method_name(&self) -> &str {
+        self.name
+    }
+
+    fn is_generic(&self) -> bool {
+        self.generic
+    }
+
+    fn params_formatter(&self) -> Option<fn(&I) -> String> {
+        self.format
+    }
+}
+
+impl<M: MockMethod<I, O, ID>, I, O, const ID: u64> AsMethodKey<I, O> for MockKey<M, ID> {
+    fn method_name(&self) -> &str {
+        M::NAME
+    }
+
+    fn params_formatter(&self) -> Option<fn(&I) -> String> {
+        M::params_formatter()
+    }
+}
+
+impl<I, O> AsMethodKey<I, O> for &str {
+    fn method_name(&self) -> &str {
+        self
+    }
+}
+
+impl<I, O> AsMethodKey<I, O> for &String {
+    fn method_name(&self) -> &str {
+        self
+    }
+}
+
+impl<I, O> AsMethodKey<I, O> for String {
+    fn method_name(&self) -> &str {
+        self
+    }
+}
+
+/// The parameter and return types of a method, remembered so that mismatches
+/// between expectations and calls can be reported clearly.
+#[derive(Clone, Copy, Debug)]
+pub struct TypeSig {
+    input: TypeId,
+    output: TypeId,
+    input_name: &'static str,
+    output_name: &'static str
+}
+
+impl TypeSig {
+    pub fn of<I, O>() -> Self where
+        I: 'static,
+        O: 'static
+    {
+        TypeSig {
+            input: TypeId::of::<I>(),
+            output: TypeId::of::<O>(),
+            input_name: any::type_name::<I>(),
+            output_name: any::type_name::<O>()
+        }
+    }
+}
+
+impl PartialEq for TypeSig {
+    fn eq(&self, other: &TypeSig) -> bool {
+        self.input == other.input && self.output == other.output
+    }
+}
+
+impl fmt::Display for TypeSig {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "I = {}, O = {}", self.input_name, self.output_name)
+    }
+}
+
 pub(crate) struct MethodSig<I, O> {
     pub(crate) name: MethodName,
     pub(crate) _types: MethodTypes<I, O>
@@ -36,7 +302,7 @@ pub(crate) struct MethodSig<I, O> {
 /// From here, use this struct's methods to set the number of calls expected.
 #[must_use]
 pub struct Method<'a, I, O> {
-    store: &'a mut ExpectationStore,
+    store: &'a ExpectationStore,
     sig: MethodSig<I, O>,
 }
 
@@ -44,13 +310,8 @@ impl<'a, I, O> Method<'a, I, O> where
     I: 'static,
     O: 'static
 {
-    pub(crate) fn new<S: ToString>(store: &'a mut ExpectationStore, name: S) -> Self {
+    pub(crate) fn new<S: ToString>(store: &'a ExpectationStore, name: S) -> Self {
         let name = name.to_string();
-        // Bail if there's already an expectation for a method with this name in the current era
-        if store.has_expectation_for_method_in_current_era(&name) {
-            panic!("Only one expectation can be set for method '{}' per era - use .then() to create a new era before adding another expectation.", name);
-        }
-
         let types = MethodTypes {
             input: PhantomData,
             output: PhantomData
@@ -77,9 +338,35 @@ impl<'a, I, O> Method<'a, I, O> where
 
     /// You expect this method to be called `calls` number of times. 
     pub fn called_times(self, calls: i64) -> TrackedMethod<'a, I, O> {
+        self.called_with_count(Times::new(calls))
+    }
+
+    /// You expect this method to be called `calls` or more times.
+    ///
+    /// For the purposes of advancing Eras, this is complete as soon as the
+    /// method has been called `calls` times.
+    pub fn called_at_least(self, calls: i64) -> TrackedMethod<'a, I, O> {
+        self.called_with_count(Times::at_least(calls))
+    }
+
+    /// You expect this method to be called `calls` or fewer times.
+    pub fn called_at_most(self, calls: i64) -> TrackedMethod<'a, I, O> {
+        self.called_with_count(Times::at_most(calls))
+    }
+
+    /// You expect this method to be called a number of times within the given
+    /// range, inclusive.
+    ///
+    /// For the purposes of advancing Eras, this is complete as soon as the
+    /// lower bound of the range has been reached.
+    pub fn called_between(self, calls: RangeInclusive<i64>) -> TrackedMethod<'a, I, O> {
+        self.called_with_count(Times::between(*calls.start(), *calls.end()))
+    }
+
+    fn called_with_count(self, times: Times) -> TrackedMethod<'a, I, O> {
         // Create an expectation that counts a certain number of calls.
         let mut exp: Expectation<I, O> = Expectation::new(&self.sig.name);
-        exp.constrain(Times::new(calls));
+        exp.constrain(times);
 
         // Add the expectation to the store.
         let id = self.store.add(exp);
@@ -121,6 +408,9 @@ impl<'a, I, O> TrackedMethod<'a, I, O> where
     ///
     /// If it returns `false` when its `validate()` method is called, the
     /// expectation will be invalidated.
+    ///
+    /// When more than one expectation is set for a method in the same Era,
+    /// each call is given to the first one whose validators accept it.
     pub fn with<V>(self, validator: V) -> Self where
         V: Validator<I> + 'static
     {
@@ -133,16 +423,111 @@ impl<'a, I, O> TrackedMethod<'a, I, O> where
     ///
     /// The primary use for this is to modify parameters passed as mutable references.
     pub fn modifying<F>(self, modification_behavior: F) -> Self where
-        F: 'static + FnMut(&mut I)
+        F: 'static + FnMut(&mut I) + MaybeSend
     {
         self.method.store.get_mut::<I, O>(self.id).set_modification(modification_behavior);
         self
     }
 
     pub fn returning<F>(self, result_behavior: F) -> Self where
-        F: 'static + FnMut(I) -> O
+        F: 'static + FnMut(I) -> O + MaybeSend
     {
         self.method.store.get_mut::<I, O>(self.id).set_return(result_behavior);
         self
     }
+
+    /// Return `O::default()` every time the method is called.
+    pub fn returning_default(self) -> Self where
+        O: Default
+    {
+        self.returning(|_| O::default())
+    }
+
+    /// Return a clone of `value` every time the method is called.
+    pub fn returning_const(self, value: O) -> Self where
+        O: Clone + MaybeSend
+    {
+        self.returning(move |_| value.clone())
+    }
+
+    /// Return each of `values` in turn, one per call.
+    ///
+    /// Panics if the method is called again after all of them have been returned.
+    pub fn returning_in_order(self, values: Vec<O>) -> Self where
+        O: MaybeSend
+    {
+        let name = self.method.sig.name.clone();
+        let mut values = values.into_iter();
+        self.returning(move |_| {
+            match values.next() {
+                Some(value) => value,
+                None => panic!("`{}` was called more times than there are values to return.", name)
+            }
+        })
+    }
+
+    /// Return the parameters that the method was called with, converted into
+    /// the return type.
+    pub fn returning_arg(self) -> Self where
+        I: Into<O>
+    {
+        self.returning(|params: I| params.into())
+    }
+
+    /// Panic with the given message when the method is called.
+    pub fn panicking<S: ToString>(self, message: S) -> Self {
+        let message = message.to_string();
+        self.returning(move |_| panic!("{}", message))
+    }
+
+    /// For `async` methods, return a future that stays pending until the test
+    /// resolves it with the returned `Resolver`.
+    ///
+    /// Only one such future is returned; calling the method again panics.
+    pub fn returning_pending(self) -> Resolver<O> where
+        O: MaybeSend
+    {
+        let name = self.method.sig.name.clone();
+        let (resolver, future) = MockFuture::pending();
+        let mut future = Some(future);
+        self.method.store.get_mut::<I, O>(self.id).set_future_return(move |_| {
+            match future.take() {
+                Some(future) => future,
+                None => panic!("`{}` was called again after its pending future was returned.", name)
+            }
+        });
+        resolver
+    }
+
+    /// Add this expectation to the end of the given `Sequence`.
+    ///
+    /// This method must then be called for the first time only after every
+    /// expectation that was added to the `Sequence` before it has been called,
+    /// even if those were set on other mock objects.
+    pub fn in_sequence(self, sequence: &Sequence) -> Self {
+        let description = self.method.store.describe(&self.method.sig.name);
+        let constraint = InSequence::new(sequence, description);
+        self.method.store.get_mut::<I, O>(self.id).constrain(constraint);
+        self
+    }
+}
+
+impl<'a, I, T, E> TrackedMethod<'a, I, Result<T, E>> where
+    I: 'static,
+    T: 'static,
+    E: 'static
+{
+    /// Return `Ok` with a clone of `value` every time the method is called.
+    pub fn returning_ok(self, value: T) -> Self where
+        T: Clone + MaybeSend
+    {
+        self.returning(move |_| Ok(value.clone()))
+    }
+
+    /// Return `Err` with a clone of `err` every time the method is called.
+    pub fn returning_err(self, err: E) -> Self where
+        E: Clone + MaybeSend
+    {
+        self.returning(move |_| Err(err.clone()))
+    }
 }
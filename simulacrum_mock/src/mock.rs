@@ -1,30 +1,110 @@
 //! Mock object internals. You can use this API to construct mock objects manually.
 
+use simulacrum_shared::MaybeSend;
+
+use std::any::{self, TypeId};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fmt;
 use std::thread;
 
-use super::method::Method;
+use super::expectation::VerificationReport;
+use super::future::MockFuture;
+use super::method::{AsMethodKey, Method};
+use super::record::{self, CallRecord};
 use super::store::ExpectationStore;
 
-#[derive(Default)]
+// Expectations are shared with functions without `self`, so they need to be
+// `Send` with the `sync` feature.
+#[cfg(feature = "sync")]
+type Shared<T> = ::std::sync::Arc<T>;
+
+#[cfg(not(feature = "sync"))]
+type Shared<T> = ::std::rc::Rc<T>;
+
+thread_local! {
+    // Expectations for functions without `self`, by the type they belong to.
+    static STATICS: RefCell<HashMap<TypeId, Shared<ExpectationStore>>> = RefCell::new(HashMap::new());
+}
+
 pub struct Expectations {
-    store: ExpectationStore
+    store: Shared<ExpectationStore>,
+    // The type whose functions without `self` use these expectations, if any.
+    statics: Option<TypeId>,
+    // Only the owner verifies the expectations when it's dropped.
+    owner: bool
 }
 
 impl Expectations {
     /// Create a new `Expectations` instance. Call this when your mock object is created.
     pub fn new() -> Self {
         Expectations {
-            store: ExpectationStore::new()
+            store: Shared::new(ExpectationStore::new()),
+            statics: None,
+            owner: true
         }
     }
 
+    /// Same as `new()`, but names the mock object so that error messages that
+    /// involve several mock objects, like those from a `Sequence`, can tell
+    /// them apart.
+    pub fn with_name<S: ToString>(mock_name: S) -> Self {
+        Expectations {
+            store: Shared::new(ExpectationStore::with_name(mock_name)),
+            statics: None,
+            owner: true
+        }
+    }
+
+    /// Also use these expectations for functions of `T` that don't take `self`,
+    /// like `fn create() -> Self`, on the current thread. They stay installed
+    /// until this instance is dropped.
+    ///
+    /// Panics if expectations are already installed for `T` on this thread.
+    pub fn install_static<T: 'static>(mut self) -> Self {
+        let id = TypeId::of::<T>();
+        STATICS.with(|statics| {
+            let mut statics = statics.borrow_mut();
+            if statics.contains_key(&id) {
+                panic!("Expectations for functions of `{}` are already installed on this thread.", any::type_name::<T>());
+            }
+            statics.insert(id, self.store.clone());
+        });
+        self.statics = Some(id);
+        self
+    }
+
+    /// Call `f` with the expectations installed for `T` by `install_static()`.
+    /// Use this to implement functions that don't take `self`.
+    ///
+    /// Panics if no expectations are installed for `T` on this thread.
+    pub fn with_static<T, R, F>(f: F) -> R where
+        T: 'static,
+        F: FnOnce(&Expectations) -> R
+    {
+        let store = STATICS.with(|statics| statics.borrow().get(&TypeId::of::<T>()).cloned());
+        let store = store.unwrap_or_else(|| {
+            panic!("No expectations are installed for functions of `{}` on this thread.", any::type_name::<T>())
+        });
+        f(&Expectations {
+            store,
+            statics: None,
+            owner: false
+        })
+    }
+
     /// Returns a `Method` struct which you can use to add expectations for the 
     /// method with the given name.
-    pub fn expect<I, O>(&mut self, name: &str) -> Method<I, O> where
+    ///
+    /// The method can be given as a `MethodKey`, which lets the compiler check
+    /// that its types match the ones used with `was_called()`, or as a plain name.
+    /// Generic methods should use `MethodKey::generic()` for both.
+    pub fn expect<I, O>(&mut self, key: impl AsMethodKey<I, O>) -> Method<'_, I, O> where
         I: 'static,
         O: 'static
     {
-        Method::new(&mut self.store, name)
+        self.note_generic(&key);
+        Method::new(&self.store, key.method_name())
     }
 
     /// Begin a new Era. Expectations in one Era must be met before expectations 
@@ -40,36 +120,160 @@ impl Expectations {
         self
     }
 
-    /// When a tracked method is called on the mock object, call this with the method's name
+    /// Turn on strict mode, where calls to methods with no matching expectation
+    /// are errors. Once the final Era is complete, that includes calls to
+    /// expectations that have already been called their maximum number of times.
+    ///
+    /// By default such calls are ignored.
+    pub fn strict(&mut self) -> &mut Self {
+        self.store.set_strict(true);
+        self
+    }
+
+    /// Handle calls to the method with the given name with `delegate_behavior`
+    /// whenever no expectation specifies a return value with `returning()`.
+    ///
+    /// Constraints on the method's expectations are still checked. This is
+    /// typically used to forward calls to a real implementation.
+    pub fn delegate<I, O, F>(&mut self, key: impl AsMethodKey<I, O>, delegate_behavior: F) -> &mut Self where
+        I: 'static,
+        O: 'static,
+        F: 'static + FnMut(I) -> O + MaybeSend
+    {
+        self.store.set_delegate(key.method_name(), delegate_behavior);
+        self
+    }
+
+    /// Start recording every call made to the mock object, whether or not it
+    /// was expected. Use `call_log()` to inspect the calls afterwards.
+    pub fn record_calls(&mut self) -> &mut Self {
+        self.store.record_calls();
+        self
+    }
+
+    /// Start recording calls, and also keep a copy of the parameters of every
+    /// call to the method with the given name. Use `calls()` to get them.
+    pub fn record_params<I>(&mut self, name: &str) -> &mut Self where
+        I: Clone + MaybeSend + 'static
+    {
+        self.store.record_params(name, record::capture_params::<I>());
+        self
+    }
+
+    /// Returns every call recorded since `record_calls()` was used, in order.
+    pub fn call_log(&self) -> Vec<CallRecord> {
+        self.store.call_log()
+    }
+
+    /// Returns the parameters of every call to the method with the given name,
+    /// in order.
+    ///
+    /// Panics if `record_params()` wasn't used for this method.
+    pub fn calls<I>(&self, name: &str) -> Vec<I> where
+        I: Clone + 'static
+    {
+        self.store.calls::<I>(name)
+    }
+
+    /// When a tracked method is called on the mock object, call this with the method's key or name
     /// in order to tell the `Expectations` that the method was called.
     ///
     /// Unlike `was_called_returning`, this method does not return a value.
-    pub fn was_called<I, O>(&self, name: &str, params: I) where
+    pub fn was_called<I, O>(&self, key: impl AsMethodKey<I, O>, params: I) where
         I: 'static,
         O: 'static
     {
+        self.note_generic(&key);
         self.store
-            .matcher_for::<I, O>(name)
+            .matcher_for::<I, O>(key.method_name())
+            .with_format(key.params_formatter())
             .was_called(params);
     }
 
+    /// Same as the `was_called` method, but also runs the closure given to
+    /// `TrackedMethod.returning()`, or the method's delegate, discarding the
+    /// result. This lets behaviors like `panicking()` work for methods that
+    /// don't return anything.
+    pub fn was_called_responding<I, O>(&self, key: impl AsMethodKey<I, O>, params: I) where
+        I: 'static,
+        O: 'static
+    {
+        self.note_generic(&key);
+        self.store
+            .matcher_for::<I, O>(key.method_name())
+            .with_format(key.params_formatter())
+            .was_called_responding(params);
+    }
+
     /// Same as the `was_called` method, but also returns the result.
-    pub fn was_called_returning<I, O>(&self, name: &str, params: I) -> O where
+    pub fn was_called_returning<I, O>(&self, key: impl AsMethodKey<I, O>, params: I) -> O where
         I: 'static,
         O: 'static
     {
+        self.note_generic(&key);
         self.store
-            .matcher_for::<I, O>(name)
+            .matcher_for::<I, O>(key.method_name())
+            .with_format(key.params_formatter())
             .was_called_returning(params)
     }
 
+    /// Same as the `was_called_returning` method, but for `async` methods. Returns
+    /// a future that resolves to the result.
+    pub fn was_called_async<I, O>(&self, key: impl AsMethodKey<I, O>, params: I) -> MockFuture<O> where
+        I: 'static,
+        O: 'static
+    {
+        self.note_generic(&key);
+        self.store
+            .matcher_for::<I, O>(key.method_name())
+            .with_format(key.params_formatter())
+            .was_called_async(params)
+    }
+
+    /// Verify every expectation in every Era without panicking, returning a
+    /// report of all of the ones that are unmet.
+    pub fn verify_all(&self) -> Result<(), VerificationReport> {
+        self.store.verify_all()
+    }
+
+    /// Verify all expectations so far, then remove them so that new ones can be
+    /// set up for the rest of the test.
+    ///
+    /// The expectations are removed even if verification fails.
+    pub fn checkpoint(&mut self) -> Result<(), VerificationReport> {
+        let result = self.verify_all();
+        self.store.clear();
+        result
+    }
+
+    // Keep expectations for each instantiation of a generic method apart.
+    fn note_generic<I, O>(&self, key: &impl AsMethodKey<I, O>) {
+        if key.is_generic() {
+            self.store.set_generic(key.method_name());
+        }
+    }
+
     fn verify(&self) {
-        if let Err(e) = self.store.verify() {
-            panic!("{}", e);
+        if let Err(report) = self.verify_all() {
+            panic!("{}", report);
         }
     }
 }
 
+impl Default for Expectations {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl fmt::Debug for Expectations {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Expectations")
+            .field("mock_name", &self.store.mock_name())
+            .finish()
+    }
+}
+
 impl Drop for Expectations {
     /// All expectations will be verified when the mock object is dropped, 
     /// panicking if any of them are unmet.
@@ -77,7 +281,10 @@ impl Drop for Expectations {
     /// In the case where the Expectations object is being dropped because the
     /// thread is _already_ panicking, the Expectations object is not verified.
     fn drop(&mut self) {
-        if !thread::panicking() {
+        if let Some(id) = self.statics.take() {
+            let _ = STATICS.try_with(|statics| statics.borrow_mut().remove(&id));
+        }
+        if self.owner && !thread::panicking() {
             self.verify();
         }
     }
@@ -87,9 +294,14 @@ impl Drop for Expectations {
 mod tests {
     use simulacrum_user::*;
 
-    use std::panic;
-
     use super::*;
+    use future::{block_on, poll_once};
+    use method::{method_id, MethodKey, MockKey, MockMethod};
+    use simulacrum_shared::{Borrowed, BorrowedMut, Scope};
+    use sequence::Sequence;
+
+    use std::panic;
+    use std::sync::{Arc, Mutex};
 
     #[test]
     fn test_called_once() {
@@ -160,6 +372,64 @@ mod tests {
         e.was_called::<(), ()>("mega", ());
     }
 
+    #[test]
+    fn test_called_at_least() {
+        let mut e = Expectations::new();
+        e.expect::<(), ()>("zap").called_at_least(2);
+
+        e.was_called::<(), ()>("zap", ());
+        e.was_called::<(), ()>("zap", ());
+        e.was_called::<(), ()>("zap", ());
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_called_at_least_fail() {
+        let mut e = Expectations::new();
+        e.expect::<(), ()>("zap").called_at_least(2);
+
+        // Panic: "zap" should have been called at least twice, but was only called once
+        e.was_called::<(), ()>("zap", ());
+    }
+
+    #[test]
+    fn test_called_at_most() {
+        let mut e = Expectations::new();
+        e.expect::<(), ()>("zap").called_at_most(2);
+
+        e.was_called::<(), ()>("zap", ());
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_called_at_most_fail() {
+        let mut e = Expectations::new();
+        e.expect::<(), ()>("zap").called_at_most(1);
+
+        e.was_called::<(), ()>("zap", ());
+        // Panic: "zap" should have been called at most once
+        e.was_called::<(), ()>("zap", ());
+    }
+
+    #[test]
+    fn test_called_between() {
+        let mut e = Expectations::new();
+        e.expect::<(), ()>("zap").called_between(1..=3);
+
+        e.was_called::<(), ()>("zap", ());
+        e.was_called::<(), ()>("zap", ());
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_called_between_fail() {
+        let mut e = Expectations::new();
+        e.expect::<(), ()>("zap").called_between(2..=3);
+
+        // Panic: "zap" should have been called at least twice, but was only called once
+        e.was_called::<(), ()>("zap", ());
+    }
+
     #[test]
     fn test_param() {
         let mut e = Expectations::new();
@@ -188,6 +458,371 @@ mod tests {
         assert_eq!(r, 5);
     }
 
+    #[test]
+    fn test_returning_default() {
+        let mut e = Expectations::new();
+        e.expect::<(), String>("boye").called_any().returning_default();
+
+        assert_eq!(e.was_called_returning::<(), String>("boye", ()), "");
+    }
+
+    #[test]
+    fn test_returning_const() {
+        let mut e = Expectations::new();
+        e.expect::<(), String>("boye").called_any().returning_const("yo".to_owned());
+
+        assert_eq!(e.was_called_returning::<(), String>("boye", ()), "yo");
+        assert_eq!(e.was_called_returning::<(), String>("boye", ()), "yo");
+    }
+
+    #[test]
+    fn test_returning_in_order() {
+        let mut e = Expectations::new();
+        e.expect::<(), i32>("boye").called_times(2).returning_in_order(vec![1, 2]);
+
+        assert_eq!(e.was_called_returning::<(), i32>("boye", ()), 1);
+        assert_eq!(e.was_called_returning::<(), i32>("boye", ()), 2);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_returning_in_order_run_out() {
+        let mut e = Expectations::new();
+        e.expect::<(), i32>("boye").called_any().returning_in_order(vec![1]);
+
+        e.was_called_returning::<(), i32>("boye", ());
+        // Panic: No values left to return
+        e.was_called_returning::<(), i32>("boye", ());
+    }
+
+    #[test]
+    fn test_returning_ok_err() {
+        let mut e = Expectations::new();
+        e.expect::<(), Result<i32, String>>("yup").called_once().returning_ok(5);
+        e.expect::<(), Result<i32, String>>("nope").called_once().returning_err("bad".to_owned());
+
+        assert_eq!(e.was_called_returning::<(), Result<i32, String>>("yup", ()), Ok(5));
+        assert_eq!(e.was_called_returning::<(), Result<i32, String>>("nope", ()), Err("bad".to_owned()));
+    }
+
+    #[test]
+    fn test_returning_arg() {
+        let mut e = Expectations::new();
+        e.expect::<i32, i64>("echo").called_once().returning_arg();
+
+        assert_eq!(e.was_called_returning::<i32, i64>("echo", 5), 5);
+    }
+
+    #[test]
+    #[should_panic(expected = "kaboom")]
+    fn test_panicking() {
+        let mut e = Expectations::new();
+        e.expect::<(), ()>("boom").called_once().panicking("kaboom");
+
+        // Panic: `returning` behaviors run for methods that don't return too
+        e.was_called_responding::<(), ()>("boom", ());
+    }
+
+    #[test]
+    fn test_was_called_skips_returning() {
+        let mut e = Expectations::new();
+        e.expect::<(), ()>("boom").called_once().panicking("kaboom");
+        e.delegate::<(), (), _>("boom", |_| panic!("delegated"));
+
+        // `was_called` only checks the expectation, like it always has
+        e.was_called::<(), ()>("boom", ());
+        assert!(e.verify_all().is_ok());
+    }
+
+    #[test]
+    fn test_panicking_verify_after() {
+        let mut e = Expectations::new();
+        e.expect::<(), i32>("boom").called_once().panicking("kaboom");
+        e.expect::<(), ()>("later").called_once();
+
+        let result = panic::catch_unwind(panic::AssertUnwindSafe(|| {
+            e.was_called_returning::<(), i32>("boom", ());
+        }));
+        assert!(result.is_err());
+
+        // The panic doesn't leave the mock unusable
+        e.was_called::<(), ()>("later", ());
+        assert!(e.verify_all().is_ok());
+    }
+
+    #[test]
+    fn test_borrowed_params() {
+        let mut e = Expectations::new();
+        e.expect::<(Borrowed<str>, BorrowedMut<Vec<i32>>), usize>("push")
+         .called_once()
+         .with(passes(|&(ref name, _): &(Borrowed<str>, BorrowedMut<Vec<i32>>)| name.with(|name| name == "hi")))
+         .modifying(|&mut (_, ref mut list)| list.with_mut(|list| list.push(3)))
+         .returning(|(name, list)| name.with(|name| name.len()) + list.with(|list| list.len()));
+
+        let name = String::from("hi");
+        let mut list = vec![1, 2];
+        let result = Scope::with(|scope| {
+            e.was_called_returning::<(Borrowed<str>, BorrowedMut<Vec<i32>>), usize>("push", (scope.borrow(name.as_str()), scope.borrow_mut(&mut list)))
+        });
+
+        assert_eq!(result, 5);
+        assert_eq!(list, vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn test_async_returning() {
+        let mut e = Expectations::new();
+        e.expect::<i32, i32>("fetch").called_once().returning(|x| x + 1);
+
+        let future = e.was_called_async::<i32, i32>("fetch", 1);
+        assert_eq!(block_on(future), 2);
+    }
+
+    #[test]
+    fn test_async_unit() {
+        let mut e = Expectations::new();
+        e.expect::<(), ()>("reset").called_once();
+
+        // No return closure is needed for methods that return ()
+        block_on(e.was_called_async::<(), ()>("reset", ()));
+    }
+
+    #[test]
+    fn test_async_pending() {
+        let mut e = Expectations::new();
+        let resolver = e.expect::<(), &'static str>("fetch").called_once().returning_pending();
+
+        let mut future = e.was_called_async::<(), &'static str>("fetch", ());
+        assert!(poll_once(&mut future).is_pending());
+
+        resolver.resolve("done");
+        assert_eq!(block_on(future), "done");
+    }
+
+    #[test]
+    #[should_panic(expected = "called again after its pending future was returned")]
+    fn test_async_pending_twice() {
+        let mut e = Expectations::new();
+        let _resolver = e.expect::<(), i32>("fetch").called_any().returning_pending();
+
+        e.was_called_async::<(), i32>("fetch", ());
+        // Panic: There is only one pending future
+        e.was_called_async::<(), i32>("fetch", ());
+    }
+
+    #[test]
+    fn test_method_key() {
+        const GOOP: MethodKey<bool, u32> = MethodKey::new("goop");
+
+        let mut e = Expectations::new();
+        e.expect(GOOP).called_once().with(true).returning(|_| 5);
+
+        assert_eq!(e.was_called_returning(GOOP, true), 5);
+    }
+
+    #[test]
+    fn test_method_key_and_name() {
+        const GOOP: MethodKey<bool, u32> = MethodKey::new("goop");
+
+        let mut e = Expectations::new();
+        e.expect(GOOP).called_once().returning(|_| 5);
+
+        // Keys and names refer to the same methods
+        assert_eq!(e.was_called_returning::<bool, u32>("goop", true), 5);
+    }
+
+    #[test]
+    fn test_mock_key() {
+        struct CoolMock;
+        impl MockMethod<bool, u32, { method_id("goop") }> for CoolMock {
+            const NAME: &'static str = "goop";
+        }
+        const GOOP: MockKey<CoolMock, { method_id("goop") }> = MockKey::new();
+
+        let mut e = Expectations::new();
+        e.expect(GOOP).called_once().with(true).returning(|_| 5);
+
+        // Mock keys refer to the method by its name too
+        assert_eq!(e.was_called_returning::<bool, u32>("goop", true), 5);
+    }
+
+    #[test]
+    fn test_generic_method() {
+        let mut e = Expectations::new();
+        e.expect::<i32, ()>(MethodKey::generic("put")).called_once().with(5);
+        e.expect::<String, ()>(MethodKey::generic("put")).called_once().with("five".to_owned());
+        e.expect::<bool, bool>(MethodKey::generic("put")).called_never();
+
+        // Each instantiation is checked separately
+        e.was_called::<String, ()>(MethodKey::generic("put"), "five".to_owned());
+        e.was_called::<i32, ()>(MethodKey::generic("put"), 5);
+    }
+
+    #[test]
+    fn test_generic_method_returning() {
+        let mut e = Expectations::new();
+        e.expect::<i32, i32>(MethodKey::generic("get")).called_once().returning(|x| x + 1);
+        e.expect::<u8, u8>(MethodKey::generic("get")).called_once().returning(|x| x * 2);
+
+        assert_eq!(e.was_called_returning::<u8, u8>(MethodKey::generic("get"), 4), 8);
+        assert_eq!(e.was_called_returning::<i32, i32>(MethodKey::generic("get"), 4), 5);
+    }
+
+    #[test]
+    #[should_panic(expected = "Called with unexpected types:\n  Expected: `I = i32, O = ()` or `I = alloc::string::String, O = ()`\n  Received: I = bool, O = ()")]
+    fn test_generic_method_unexpected_type() {
+        let mut e = Expectations::new();
+        e.expect::<i32, ()>(MethodKey::generic("put")).called_any();
+        e.expect::<String, ()>(MethodKey::generic("put")).called_any();
+
+        // Panic: There are no expectations for `put::<bool>()`
+        e.was_called::<bool, ()>(MethodKey::generic("put"), true);
+    }
+
+    #[test]
+    #[should_panic(expected = "Can't return a value for method `get` called with `I = bool, O = bool`, which only has expectations for `I = i32, O = i32`.")]
+    fn test_generic_method_returning_unexpected_type() {
+        let mut e = Expectations::new();
+        e.expect::<i32, i32>(MethodKey::generic("get")).called_any().returning(|x| x);
+
+        // Panic: There are no expectations for `get::<bool>()`
+        e.was_called_returning::<bool, bool>(MethodKey::generic("get"), true);
+    }
+
+    struct Widget;
+
+    #[test]
+    fn test_static() {
+        let mut e = Expectations::new().install_static::<Widget>();
+        e.expect::<u16, u16>("create").called_once().with(80).returning(|port| port + 1);
+
+        let result = Expectations::with_static::<Widget, _, _>(|e| {
+            e.was_called_returning::<u16, u16>("create", 80)
+        });
+        assert_eq!(result, 81);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_static_fail() {
+        let mut e = Expectations::new().install_static::<Widget>();
+
+        // Panic: "create" should have been called once, but was never called
+        e.expect::<u16, u16>("create").called_once();
+    }
+
+    #[test]
+    #[should_panic(expected = "No expectations are installed for functions of `simulacrum_mock::mock::tests::Widget` on this thread.")]
+    fn test_static_not_installed() {
+        Expectations::with_static::<Widget, _, _>(|e| e.was_called::<(), ()>("reset", ()));
+    }
+
+    #[test]
+    #[should_panic(expected = "Expectations for functions of `simulacrum_mock::mock::tests::Widget` are already installed on this thread.")]
+    fn test_static_installed_twice() {
+        let _first = Expectations::new().install_static::<Widget>();
+        let _second = Expectations::new().install_static::<Widget>();
+    }
+
+    #[test]
+    fn test_static_uninstalled_on_drop() {
+        {
+            let mut e = Expectations::new().install_static::<Widget>();
+            e.expect::<(), ()>("reset").called_once();
+            Expectations::with_static::<Widget, _, _>(|e| e.was_called::<(), ()>("reset", ()));
+        }
+
+        // Installing them again works once the first ones are gone
+        let mut e = Expectations::new().install_static::<Widget>();
+        e.expect::<(), ()>("reset").called_never();
+    }
+
+    #[test]
+    #[should_panic(expected = "Mismatched types for `goop`: it was registered with `I = bool, O = u32`, but used with `I = i32, O = u32`.")]
+    fn test_mismatched_types_call() {
+        let mut e = Expectations::new();
+        e.expect::<bool, u32>("goop").called_once();
+
+        // Panic: Called with different types than expected
+        e.was_called_returning::<i32, u32>("goop", 5);
+    }
+
+    #[test]
+    #[should_panic(expected = "Mismatched types for `CoolMock::goop`: it was registered with `I = bool, O = u32`, but used with `I = bool, O = ()`.")]
+    fn test_mismatched_types_expect() {
+        let mut e = Expectations::with_name("CoolMock");
+        e.expect::<bool, u32>("goop").called_once();
+
+        // Panic: Expected with different types than before
+        e.expect::<bool, ()>("goop").called_once();
+    }
+
+    #[test]
+    fn test_debug() {
+        let e = Expectations::with_name("CoolMock");
+
+        assert_eq!(format!("{:?}", e), "Expectations { mock_name: \"CoolMock\" }");
+    }
+
+    #[test]
+    #[should_panic(expected = "Mismatched types for `goop`: it was registered with `I = bool, O = u32`, but used with `I = (), O = u32`.")]
+    fn test_mismatched_types_delegate() {
+        let mut e = Expectations::new();
+        e.expect::<bool, u32>("goop").called_once();
+
+        // Panic: Delegate has different types than the expectation
+        e.delegate::<(), u32, _>("goop", |_| 5);
+    }
+
+    #[test]
+    #[should_panic(expected = "Mismatched types for `zing`: it was registered with `I = i32`, but used with `I = bool`.")]
+    fn test_mismatched_types_calls() {
+        let mut e = Expectations::new();
+        e.record_params::<i32>("zing");
+
+        // Panic: Parameters were recorded as a different type
+        e.calls::<bool>("zing");
+    }
+
+    #[test]
+    fn test_delegate() {
+        let mut e = Expectations::new();
+        e.delegate::<i32, i32, _>("double", |x| x * 2);
+        e.expect::<i32, i32>("double").called_times(2);
+        e.expect::<(), i32>("answer").called_once().returning(|_| 42);
+        e.delegate::<(), i32, _>("answer", |_| 0);
+
+        assert_eq!(e.was_called_returning::<i32, i32>("double", 2), 4);
+        assert_eq!(e.was_called_returning::<i32, i32>("double", 5), 10);
+        // `returning` takes precedence over the delegate
+        assert_eq!(e.was_called_returning::<(), i32>("answer", ()), 42);
+    }
+
+    #[test]
+    fn test_delegate_unit() {
+        let calls = Arc::new(Mutex::new(0));
+        let delegate_calls = calls.clone();
+        let mut e = Expectations::new();
+        e.delegate::<(), (), _>("bump", move |_| *delegate_calls.lock().unwrap() += 1);
+
+        // Calls without an expectation are forwarded too
+        e.was_called_responding::<(), ()>("bump", ());
+        e.was_called_responding::<(), ()>("bump", ());
+        assert_eq!(*calls.lock().unwrap(), 2);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_delegate_constraints_checked() {
+        let mut e = Expectations::new();
+        e.delegate::<i32, i32, _>("double", |x| x * 2);
+        e.expect::<i32, i32>("double").called_once();
+
+        e.was_called_returning::<i32, i32>("double", 2);
+        e.was_called_returning::<i32, i32>("double", 2);
+        // Panic: "double" was called too many times
+    }
+
     #[test]
     #[should_panic]
     fn test_returning_no_matches() {
@@ -345,16 +980,56 @@ mod tests {
     }
 
     #[test]
-    fn test_one_expectation_per_era() {
+    fn test_multiple_expectations_per_era() {
         let mut e = Expectations::new();
+        e.expect::<i32, &'static str>("get").called_once().with(1).returning(|_| "a");
+        e.expect::<i32, &'static str>("get").called_once().with(2).returning(|_| "b");
 
-        e.expect::<(), ()>("eh").called_never();
-        let result = panic::catch_unwind(
-            panic::AssertUnwindSafe(|| {
-                e.expect::<(), ()>("eh").called_never(); // Panic: only one expectation should be registered for "eh" in a given era
-            })
-        );
-        assert!(result.is_err());
+        assert_eq!(e.was_called_returning::<i32, &'static str>("get", 2), "b");
+        assert_eq!(e.was_called_returning::<i32, &'static str>("get", 1), "a");
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_multiple_expectations_per_era_counted_separately() {
+        let mut e = Expectations::new();
+        e.expect::<i32, ()>("put").called_once().with(1);
+        e.expect::<i32, ()>("put").called_once().with(2);
+
+        e.was_called::<i32, ()>("put", 1);
+        e.was_called::<i32, ()>("put", 1);
+
+        // Panic: "put" with 1 was called twice, and "put" with 2 was never called
+    }
+
+    #[test]
+    fn test_multiple_expectations_per_era_validate_once() {
+        let runs = Arc::new(Mutex::new(0));
+        let counted = runs.clone();
+        let mut e = Expectations::new();
+        e.expect::<i32, ()>("put").called_once().with(1);
+        e.expect::<i32, ()>("put").called_once().with(passes(move |&arg: &i32| {
+            *counted.lock().unwrap() += 1;
+            arg == 2
+        }));
+
+        e.was_called::<i32, ()>("put", 2);
+        e.was_called::<i32, ()>("put", 1);
+
+        // The validator picked the call with 2 and wasn't run again to count
+        // it, and the call with 1 was picked before reaching it
+        assert_eq!(*runs.lock().unwrap(), 1);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_multiple_expectations_per_era_no_match() {
+        let mut e = Expectations::new();
+        e.expect::<i32, ()>("put").called_any().with(1);
+        e.expect::<i32, ()>("put").called_any().with(2);
+
+        // Panic: Neither expectation accepts 3
+        e.was_called::<i32, ()>("put", 3);
     }
 
     #[test]
@@ -371,6 +1046,20 @@ mod tests {
         e.was_called::<(), ()>("d", ()); // Completes second era
     }
 
+    #[test]
+    fn test_at_least_completes_era_at_lower_bound() {
+        let mut e = Expectations::new();
+
+        // Expectations
+        e.expect::<(), ()>("c").called_at_least(1);
+        e.then();
+        e.expect::<(), ()>("d").called_once();
+
+        // Calls
+        e.was_called::<(), ()>("c", ()); // Completes first era
+        e.was_called::<(), ()>("d", ()); // Completes second era
+    }
+
     #[test]
     fn test_calls_ignored_after_final_era_completes() {
         let mut e = Expectations::new();
@@ -384,6 +1073,225 @@ mod tests {
         e.was_called::<(), ()>("c", ()); // Doesn't matter, all eras are complete already
     }
 
+    #[test]
+    #[should_panic]
+    fn test_strict_no_expectation() {
+        let mut e = Expectations::new();
+        e.strict();
+
+        // Panic: "c" has no expectations
+        e.was_called::<(), ()>("c", ());
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_strict_after_final_era_completes() {
+        let mut e = Expectations::new();
+        e.strict();
+
+        // Expectations
+        e.expect::<(), ()>("c").called_once();
+        e.then();
+
+        // Calls
+        e.was_called::<(), ()>("c", ()); // Completes first era
+        e.was_called::<(), ()>("c", ()); // Panic: all eras are complete already
+    }
+
+    #[test]
+    #[should_panic(expected = "Called when not expected")]
+    fn test_strict_after_final_era_completes_without_then() {
+        let mut e = Expectations::new();
+        e.strict();
+
+        // Expectations
+        e.expect::<(), ()>("c").called_once();
+
+        // Calls
+        e.was_called::<(), ()>("c", ()); // Completes the only era
+        e.was_called::<(), ()>("c", ()); // Panic: all eras are complete already
+    }
+
+    #[test]
+    fn test_strict_after_final_era_completes_unbounded() {
+        let mut e = Expectations::new();
+        e.strict();
+
+        // Expectations
+        e.expect::<(), ()>("c").called_at_least(1);
+
+        // Calls
+        e.was_called::<(), ()>("c", ()); // Completes the only era
+        e.was_called::<(), ()>("c", ()); // Still expected, since there's no upper bound
+    }
+
+    #[test]
+    fn test_verify_all() {
+        let mut e = Expectations::new();
+        e.expect::<(), ()>("c").called_once();
+        e.expect::<(), ()>("d").called_never();
+        e.then();
+        e.expect::<(), ()>("f").called_once();
+
+        e.was_called::<(), ()>("d", ());
+
+        let report = e.verify_all().unwrap_err();
+        let names: Vec<&str> = report.errors.iter().map(|e| e.method_name.as_str()).collect();
+        assert_eq!(names, vec!["c", "d", "f"]);
+
+        // Clear the unmet expectations so that they aren't verified on drop
+        assert!(e.checkpoint().is_err());
+    }
+
+    #[test]
+    fn test_checkpoint() {
+        let mut e = Expectations::new();
+        e.expect::<(), ()>("c").called_once();
+
+        e.was_called::<(), ()>("c", ());
+        assert!(e.checkpoint().is_ok());
+
+        e.expect::<(), ()>("c").called_times(2);
+
+        e.was_called::<(), ()>("c", ());
+        e.was_called::<(), ()>("c", ());
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_checkpoint_clears_expectations() {
+        let mut e = Expectations::new();
+        e.expect::<(), ()>("c").called_once();
+
+        assert!(e.checkpoint().is_err());
+
+        e.expect::<(), ()>("d").called_once();
+
+        // Panic: "d" was never called. "c" was removed by the checkpoint.
+    }
+
+    #[test]
+    fn test_call_log() {
+        let mut e = Expectations::new();
+        e.record_calls();
+        e.expect::<(), ()>("c").called_once();
+        e.then().expect::<(), ()>("d").called_once();
+
+        e.was_called::<(), ()>("c", ());
+        e.was_called::<(), ()>("x", ()); // Not expected, but still recorded
+        e.was_called::<(), ()>("d", ());
+
+        let log = e.call_log();
+        let calls: Vec<(&str, usize, usize)> = log.iter()
+            .map(|r| (r.method_name.as_str(), r.era, r.sequence))
+            .collect();
+        assert_eq!(calls, vec![("c", 0, 0), ("x", 1, 1), ("d", 1, 2)]);
+    }
+
+    #[test]
+    fn test_call_log_args() {
+        const ZING: MethodKey<(i32, bool), ()> = MethodKey::new("zing").debug();
+
+        let mut e = Expectations::new();
+        e.record_calls();
+
+        e.was_called(ZING, (5, true));
+        e.was_called::<i32, ()>("zang", 5);
+
+        let args: Vec<String> = e.call_log().into_iter().map(|r| r.args).collect();
+        assert_eq!(args, vec!["(5, true)", "<unknown>"]);
+    }
+
+    #[test]
+    fn test_debug_params() {
+        use method::{DebugParams, FormatDebug, FormatUnknown};
+        struct Opaque;
+
+        let debug = &DebugParams::<(i32, &'static str)>::new();
+        let opaque = &DebugParams::<Opaque>::new();
+        let (debug, opaque) = (debug.formatter(), opaque.formatter());
+
+        assert_eq!(debug.map(|format| format(&(5, "hi"))), Some("(5, \"hi\")".to_owned()));
+        assert!(opaque.is_none());
+    }
+
+    #[test]
+    fn test_calls() {
+        let mut e = Expectations::new();
+        e.record_params::<(i32, bool)>("zing");
+        e.expect::<(i32, bool), u32>("zing").called_any().returning(|(x, _)| x as u32);
+
+        e.was_called_returning::<(i32, bool), u32>("zing", (1, true));
+        e.was_called_returning::<(i32, bool), u32>("zing", (2, false));
+
+        assert_eq!(e.calls::<(i32, bool)>("zing"), vec![(1, true), (2, false)]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_calls_not_recorded() {
+        let mut e = Expectations::new();
+        e.record_calls();
+
+        e.was_called::<i32, ()>("zing", 1);
+
+        // Panic: Parameters for "zing" weren't recorded
+        e.calls::<i32>("zing");
+    }
+
+    #[test]
+    #[cfg(feature = "sync")]
+    fn test_send_sync() {
+
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<Expectations>();
+
+        let mut e = Expectations::new();
+        e.expect::<i32, i32>("spin").called_times(4).with(passes(|arg| *arg < 4)).returning(|arg| arg * 2);
+
+        let e = Arc::new(e);
+        let threads: Vec<_> = (0..4).map(|i| {
+            let e = e.clone();
+            thread::spawn(move || e.was_called_returning::<i32, i32>("spin", i))
+        }).collect();
+        let mut results: Vec<i32> = threads.into_iter().map(|t| t.join().unwrap()).collect();
+        results.sort();
+
+        assert_eq!(results, vec![0, 2, 4, 6]);
+    }
+
+    #[test]
+    fn test_sequence() {
+        let seq = Sequence::new();
+        let mut db = Expectations::with_name("DbMock");
+        let mut log = Expectations::with_name("LogMock");
+        db.expect::<(), ()>("begin").called_once().in_sequence(&seq);
+        log.expect::<(), ()>("write").called_once().in_sequence(&seq);
+        db.expect::<(), ()>("commit").called_once().in_sequence(&seq);
+
+        db.was_called::<(), ()>("begin", ());
+        log.was_called::<(), ()>("write", ());
+        db.was_called::<(), ()>("commit", ());
+    }
+
+    #[test]
+    fn test_sequence_out_of_order() {
+        let seq = Sequence::new();
+        let mut db = Expectations::with_name("DbMock");
+        let mut log = Expectations::with_name("LogMock");
+        db.expect::<(), ()>("begin").called_once().in_sequence(&seq);
+        log.expect::<(), ()>("write").called_once().in_sequence(&seq);
+        db.expect::<(), ()>("commit").called_once().in_sequence(&seq);
+
+        db.was_called::<(), ()>("begin", ());
+        db.was_called::<(), ()>("commit", ());
+        log.was_called::<(), ()>("write", ());
+
+        let report = db.checkpoint().unwrap_err();
+        assert_eq!(report.to_string(), "commit: Called out of sequence: `DbMock::commit` was called before `LogMock::write`.");
+        assert!(log.checkpoint().is_ok());
+    }
+
     // Note: this test is a WIP
     // Test for a bug where in the first era, if a param mismatch occurs, the
     // second era isn't evaluated.
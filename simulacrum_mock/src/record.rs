@@ -0,0 +1,62 @@
+//! Records of calls made to a mock object, for inspecting after the fact.
+
+use simulacrum_shared::MaybeSend;
+
+use std::any::{self, Any, TypeId};
+
+use super::MethodName;
+
+/// A single call made to a mock object, as recorded by `Expectations::record_calls()`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CallRecord {
+    /// The name of the method that was called.
+    pub method_name: MethodName,
+    /// The index of the Era that was being evaluated when the call was made.
+    pub era: usize,
+    /// The position of this call amongst all of the calls made to the mock object.
+    pub sequence: usize,
+    /// A `Debug` rendering of the parameters, or `<unknown>` if the method's
+    /// key can't print them. Mocks made by the macros print parameters that
+    /// implement `Debug`, and a `MethodKey` does with `.debug()`.
+    pub args: String
+}
+
+// A `CallRecord` along with a copy of the parameters, if they were captured.
+pub(crate) struct LoggedCall {
+    pub(crate) record: CallRecord,
+    pub(crate) params: Option<Box<dyn CapturedParams>>
+}
+
+// A copy of the parameters of a call. This lets them be `Send` only when
+// `MaybeSend` requires it.
+pub(crate) trait CapturedParams: MaybeSend {
+    fn as_any(&self) -> &dyn Any;
+}
+
+impl<T> CapturedParams for T where
+    T: Any + MaybeSend
+{
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+// Copies the parameters for a method so that they can be stored in the log.
+pub(crate) struct ParamsCapture {
+    pub(crate) input: TypeId,
+    pub(crate) input_name: &'static str,
+    pub(crate) copy: Box<dyn Fn(&dyn Any) -> Box<dyn CapturedParams> + Send + Sync>
+}
+
+pub(crate) fn capture_params<I>() -> ParamsCapture where
+    I: Clone + MaybeSend + 'static
+{
+    ParamsCapture {
+        input: TypeId::of::<I>(),
+        input_name: any::type_name::<I>(),
+        copy: Box::new(|params: &dyn Any| {
+            let params: I = params.downcast_ref::<I>().unwrap().clone();
+            Box::new(params)
+        })
+    }
+}
@@ -0,0 +1,88 @@
+//! Ordering of calls across several mock objects.
+
+use std::sync::{Arc, Mutex};
+
+/// A shareable ordering of expected calls, independent of Eras.
+///
+/// Add expectations from any number of mock objects to the same `Sequence`
+/// with `TrackedMethod::in_sequence()`. Each expectation in a `Sequence` must
+/// be called for the first time after every expectation that was added to it
+/// before, or it will fail.
+#[derive(Clone, Default)]
+pub struct Sequence(Arc<Mutex<Vec<Step>>>);
+
+struct Step {
+    // "MockName::method_name", for error messages
+    description: String,
+    called: bool
+}
+
+impl Sequence {
+    /// Create a new, empty `Sequence`.
+    pub fn new() -> Self {
+        Sequence(Arc::new(Mutex::new(Vec::new())))
+    }
+
+    // Add a step to the end of this sequence and return its position.
+    pub(crate) fn add(&self, description: String) -> usize {
+        let mut steps = self.0.lock().unwrap();
+        steps.push(Step {
+            description,
+            called: false
+        });
+        steps.len() - 1
+    }
+
+    // Mark the step at the given position as called. If it was called before a
+    // step that comes earlier in the sequence, returns the description of
+    // that earlier step.
+    pub(crate) fn call(&self, position: usize) -> Result<(), String> {
+        let mut steps = self.0.lock().unwrap();
+        steps[position].called = true;
+        match steps[..position].iter().find(|step| !step.called) {
+            Some(step) => Err(step.description.clone()),
+            None => Ok(())
+        }
+    }
+
+    // The description of the step at the given position.
+    pub(crate) fn description(&self, position: usize) -> String {
+        self.0.lock().unwrap()[position].description.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_in_order() {
+        let s = Sequence::new();
+        let first = s.add("A::first".to_owned());
+        let second = s.add("B::second".to_owned());
+
+        assert!(s.call(first).is_ok());
+        assert!(s.call(second).is_ok());
+    }
+
+    #[test]
+    fn test_out_of_order() {
+        let s = Sequence::new();
+        let first = s.add("A::first".to_owned());
+        let second = s.add("B::second".to_owned());
+
+        assert_eq!(s.call(second), Err("A::first".to_owned()));
+        assert!(s.call(first).is_ok());
+    }
+
+    #[test]
+    fn test_shared() {
+        let s = Sequence::new();
+        let first = s.add("A::first".to_owned());
+        let second = s.clone().add("B::second".to_owned());
+
+        assert_eq!(s.description(second), "B::second");
+        assert!(s.clone().call(first).is_ok());
+        assert!(s.call(second).is_ok());
+    }
+}
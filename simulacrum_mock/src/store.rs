@@ -1,35 +1,102 @@
+use debugit::DebugIt;
 use handlebox::HandleBox;
+use simulacrum_shared::MaybeSend;
 
+use std::any::{self, Any, TypeId};
 use std::cell::RefCell;
-use std::sync::Mutex;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::sync::{Mutex, MutexGuard};
 
 use super::{ExpectationId, MethodName};
-use super::expectation::{Constraint, Expectation, ExpectationT, ExpectationResult};
-use super::method::{MethodSig, MethodTypes};
+use super::constraint::ConstraintError;
+use super::future::MockFuture;
+use super::expectation::{Constraint, Delegate, DelegateT, Expectation, ExpectationError, ExpectationT, ExpectationResult, VerificationReport};
+use super::method::{MethodSig, MethodTypes, TypeSig};
+use super::record::{CallRecord, LoggedCall, ParamsCapture};
 
 // A thread-safe store for `Box<ExpectationT>`s, including the order that they should be
 // evaluated in (Eras).
 pub(crate) struct ExpectationStore(Mutex<Inner>);
 
 struct Inner {
+    // The name of the mock object, for error messages.
+    mock_name: String,
     current_unverified_era: usize,
     eras: Vec<Era>,
-    expectations: HandleBox<Box<ExpectationT>>
+    // Set by `verify()` when every Era is complete.
+    all_eras_complete: bool,
+    expectations: HandleBox<Box<ExpectationT>>,
+    // When set, calls that match no Expectation by name are unexpected too.
+    strict: bool,
+    // Calls that could not be matched to any single Expectation.
+    unexpected_calls: Vec<ExpectationError>,
+    // Every call made, if calls are being recorded.
+    call_log: Option<Vec<LoggedCall>>,
+    // Methods whose parameters are copied into the call log.
+    captures: HashMap<MethodName, ParamsCapture>,
+    // Behaviors for calls that no Expectation provides a return value for.
+    delegates: HashMap<MethodName, Box<dyn DelegateT>>,
+    // Generic methods, whose Expectations are told apart by their types.
+    generic_methods: HashSet<MethodName>
 }
 
 type Era = Vec<ExpectationId>;
 
+impl Inner {
+    // If the given generic method has Expectations, but none with the given
+    // types, describe the types that it does have Expectations for.
+    fn expected_types(&self, name: &str, types: TypeSig) -> Option<String> {
+        if !self.generic_methods.contains(name) {
+            return None;
+        }
+        let mut expected: Vec<TypeSig> = Vec::new();
+        for expectation in self.expectations.map.values().filter(|expectation| expectation.name() == name) {
+            let registered = expectation.types();
+            if registered == types {
+                return None;
+            }
+            if !expected.contains(&registered) {
+                expected.push(registered);
+            }
+        }
+        if expected.is_empty() {
+            None
+        } else {
+            Some(expected.iter().map(|types| format!("`{}`", types)).collect::<Vec<_>>().join(" or "))
+        }
+    }
+}
+
 impl ExpectationStore {
     pub fn new() -> Self {
+        Self::with_name("")
+    }
+
+    pub fn with_name<S: ToString>(mock_name: S) -> Self {
         let eras = vec![Era::new()];
         ExpectationStore(Mutex::new(Inner {
+            mock_name: mock_name.to_string(),
             current_unverified_era: 0,
             eras,
-            expectations: HandleBox::new()
+            all_eras_complete: false,
+            expectations: HandleBox::new(),
+            strict: false,
+            unexpected_calls: Vec::new(),
+            call_log: None,
+            captures: HashMap::new(),
+            delegates: HashMap::new(),
+            generic_methods: HashSet::new()
         }))
     }
 
-    pub fn get_mut<I, O>(&self, id: ExpectationId) -> ExpectationEditor<I, O> {
+    // Return behaviors such as `panicking()` run while the store is locked, so
+    // a panicking test would otherwise poison it for verification and `Drop`.
+    fn lock(&self) -> MutexGuard<'_, Inner> {
+        self.0.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    pub fn get_mut<I, O>(&self, id: ExpectationId) -> ExpectationEditor<'_, I, O> {
         ExpectationEditor {
             id,
             store: &self,
@@ -37,27 +104,13 @@ impl ExpectationStore {
         }
     }
 
-    pub(crate) fn has_expectation_for_method_in_current_era(&self, name: &MethodName) -> bool {
-        // If the current era is complete, move on to the next incomplete one.
-        self.advance_era();
-
-        // Lock our inner mutex
-        let inner = self.0.lock().unwrap();
-
-        // Get the current era and see if there's an expectation with this name in it 
-        for id in inner.eras.last().unwrap() {
-            if inner.expectations.get(&id).unwrap().name() == name {
-                return true;
-            }
-        }
-
-        false
-    }
-
-    pub fn matcher_for<I, O>(&self, name: &str) -> ExpectationMatcher<I, O> where
+    pub fn matcher_for<I, O>(&self, name: &str) -> ExpectationMatcher<'_, I, O> where
         I: 'static,
         O: 'static
     {
+        let types = TypeSig::of::<I, O>();
+        self.check_types(name, types);
+
         let sig = MethodSig {
             name: name.to_string(),
             _types: MethodTypes::new()
@@ -67,30 +120,85 @@ impl ExpectationStore {
         self.advance_era();
 
         // Lock our inner mutex
-        let inner = self.0.lock().unwrap();
+        let inner = self.lock();
+
+        let expected_types = inner.expected_types(name, types);
 
         // Only return ids if we have unverified Eras remaining
         if inner.current_unverified_era < inner.eras.len() {
             // Gather up ids for expectations that match this one in the current Era
             let mut ids = inner.eras.get(inner.current_unverified_era).unwrap().clone();
             ids.retain(|&id| {
-                inner.expectations.get(&id).unwrap().name() == name
+                let expectation = inner.expectations.get(&id).unwrap();
+                expectation.name() == name && expectation.types() == types
             });
 
+            // Once every Era is complete, strict mode doesn't count calls
+            // against Expectations that can't take any more, so that they are
+            // reported as unexpected instead.
+            if inner.strict && inner.all_eras_complete {
+                ids.retain(|id| !inner.expectations.get(id).unwrap().is_exhausted());
+            }
+
             ExpectationMatcher {
                 ids,
                 sig,
+                expected_types,
+                format: None,
                 store: &self
             }
         } else {
             ExpectationMatcher {
                 ids: Vec::new(),
                 sig,
+                expected_types,
+                format: None,
                 store: &self
             }
         }
     }
 
+    pub fn mock_name(&self) -> String {
+        self.lock().mock_name.clone()
+    }
+
+    // Describe a method of this mock object, e.g. "CoolTraitMock::foo".
+    pub fn describe(&self, name: &str) -> String {
+        let inner = self.lock();
+        if inner.mock_name.is_empty() {
+            name.to_string()
+        } else {
+            format!("{}::{}", inner.mock_name, name)
+        }
+    }
+
+    // Panic if the given method was registered with different types. Generic
+    // methods can be registered with any number of types.
+    fn check_types(&self, name: &str, requested: TypeSig) {
+        let registered = {
+            let inner = self.lock();
+            if inner.generic_methods.contains(name) {
+                return;
+            }
+            inner.expectations.map.values()
+                .find(|expectation| expectation.name() == name)
+                .map(|expectation| expectation.types())
+                .or_else(|| inner.delegates.get(name).map(|delegate| delegate.types()))
+        };
+        if let Some(registered) = registered {
+            if registered != requested {
+                self.mismatched_types(name, registered, requested);
+            }
+        }
+    }
+
+    fn mismatched_types<R, U>(&self, name: &str, registered: R, requested: U) -> ! where
+        R: fmt::Display,
+        U: fmt::Display
+    {
+        panic!("Mismatched types for `{}`: it was registered with `{}`, but used with `{}`.", self.describe(name), registered, requested);
+    }
+
     // If the current era is complete, move on to the next incomplete one
     #[allow(unused_must_use)]
     pub fn advance_era(&self) {
@@ -102,8 +210,10 @@ impl ExpectationStore {
     pub fn add<E>(&self, expectation: E) -> ExpectationId where
         E: ExpectationT + 'static
     {
+        self.check_types(expectation.name(), expectation.types());
+
         // Lock our inner mutex
-        let mut inner = self.0.lock().unwrap();
+        let mut inner = self.lock();
         
         // Add a new expectation
         let id = inner.expectations.add(Box::new(expectation));
@@ -114,10 +224,142 @@ impl ExpectationStore {
         id
     }
 
+    // Handle calls to the given method with `delegate_behavior` whenever no
+    // Expectation provides a return value.
+    pub fn set_delegate<I, O, F>(&self, name: &str, delegate_behavior: F) where
+        I: 'static,
+        O: 'static,
+        F: 'static + FnMut(I) -> O + MaybeSend
+    {
+        self.check_types(name, TypeSig::of::<I, O>());
+
+        let mut inner = self.lock();
+        inner.delegates.insert(name.to_string(), Box::new(Delegate::new(delegate_behavior)));
+    }
+
+    // Treat the given method as generic, so that its Expectations are told
+    // apart by their types.
+    pub fn set_generic(&self, name: &str) {
+        self.lock().generic_methods.insert(name.to_string());
+    }
+
+    // Note a call that didn't belong to any Expectation, so that it fails verification.
+    pub fn add_unexpected_call<I>(&self, name: &MethodName, params: &I, format: Option<fn(&I) -> String>) {
+        // Lock our inner mutex
+        let mut inner = self.lock();
+
+        let received_msg = render_params(params, format);
+        inner.unexpected_calls.push(ExpectationError {
+            constraint_err: ConstraintError::CallNotExpected(received_msg),
+            method_name: name.clone()
+        });
+    }
+
+    // Note a call to a generic method with types that it has no Expectations
+    // for, so that it fails verification.
+    pub fn add_unexpected_types(&self, name: &MethodName, expected_types: &str, received: TypeSig) {
+        // Lock our inner mutex
+        let mut inner = self.lock();
+
+        inner.unexpected_calls.push(ExpectationError {
+            constraint_err: ConstraintError::CallTypesNotExpected(expected_types.to_string(), received.to_string()),
+            method_name: name.clone()
+        });
+    }
+
+    // Make calls to methods without a matching Expectation fail verification.
+    pub fn set_strict(&self, strict: bool) {
+        self.lock().strict = strict;
+    }
+
+    fn is_strict(&self) -> bool {
+        self.lock().strict
+    }
+
+    // Start recording calls, if we aren't already.
+    pub fn record_calls(&self) {
+        let mut inner = self.lock();
+        if inner.call_log.is_none() {
+            inner.call_log = Some(Vec::new());
+        }
+    }
+
+    // Record calls, and also keep a copy of the parameters of calls to the given method.
+    pub fn record_params(&self, name: &str, capture: ParamsCapture) {
+        self.record_calls();
+        self.lock().captures.insert(name.to_string(), capture);
+    }
+
+    // Add a call to the log, if calls are being recorded.
+    pub fn log_call<I>(&self, name: &MethodName, params: &I, format: Option<fn(&I) -> String>) where
+        I: 'static
+    {
+        self.check_captured_type::<I>(name);
+
+        // Lock our inner mutex
+        let mut inner = self.lock();
+
+        let era = inner.current_unverified_era;
+        let captured = inner.captures.get(name).map(|capture| (capture.copy)(params));
+        if let Some(ref mut log) = inner.call_log {
+            let record = CallRecord {
+                method_name: name.clone(),
+                era,
+                sequence: log.len(),
+                args: render_params(params, format)
+            };
+            log.push(LoggedCall { record, params: captured });
+        }
+    }
+
+    // Get a copy of every recorded call.
+    pub fn call_log(&self) -> Vec<CallRecord> {
+        match self.lock().call_log {
+            Some(ref log) => log.iter().map(|call| call.record.clone()).collect(),
+            None => Vec::new()
+        }
+    }
+
+    // Get a copy of the parameters of every recorded call to the given method.
+    pub fn calls<I>(&self, name: &str) -> Vec<I> where
+        I: Clone + 'static
+    {
+        self.check_captured_type::<I>(name);
+
+        let inner = self.lock();
+
+        if !inner.captures.contains_key(name) {
+            panic!("Parameters for method `{}` were not recorded - use `record_params()` before it is called.", name);
+        }
+
+        match inner.call_log {
+            Some(ref log) => {
+                log.iter()
+                   .filter(|call| call.record.method_name == name)
+                   .filter_map(|call| call.params.as_ref())
+                   .map(|params| params.as_any().downcast_ref::<I>().unwrap().clone())
+                   .collect()
+            },
+            None => Vec::new()
+        }
+    }
+
+    // Panic if parameters for the given method are being captured as a different type.
+    fn check_captured_type<I>(&self, name: &str) where
+        I: 'static
+    {
+        let recorded = self.lock().captures.get(name)
+            .filter(|capture| capture.input != TypeId::of::<I>())
+            .map(|capture| capture.input_name);
+        if let Some(recorded) = recorded {
+            self.mismatched_types(name, format!("I = {}", recorded), format!("I = {}", any::type_name::<I>()));
+        }
+    }
+
     // Begin a new Era and make it the current one.
     pub fn new_era(&self) {
         // Lock our inner mutex
-        let mut inner = self.0.lock().unwrap();
+        let mut inner = self.lock();
 
         inner.eras.push(Vec::new());
     }
@@ -127,7 +369,7 @@ impl ExpectationStore {
         let mut status = Ok(());
 
         // Lock our inner mutex
-        let mut inner = self.0.lock().unwrap();
+        let mut inner = self.lock();
 
         let original_unverified_era = inner.current_unverified_era;
 
@@ -149,20 +391,70 @@ impl ExpectationStore {
                 }
             }
         }
+        inner.all_eras_complete = status.is_ok();
+
+        // Calls that matched no Expectation fail the store, but they shouldn't
+        // hold back Eras from completing.
+        if status.is_ok() {
+            if let Some(err) = inner.unexpected_calls.first() {
+                status = Err(err.clone());
+            }
+        }
 
         status
     }
 
+    /// Verify all expectations in this store, collecting every error instead of
+    /// stopping at the first one.
+    pub fn verify_all(&self) -> Result<(), VerificationReport> {
+        // If the current era is complete, move on to the next incomplete one.
+        self.advance_era();
+
+        // Lock our inner mutex
+        let inner = self.lock();
+
+        let mut errors = Vec::new();
+        for era in inner.eras[inner.current_unverified_era..].iter() {
+            for id in era.iter() {
+                if let Err(e) = inner.expectations.get(id).unwrap().verify() {
+                    errors.push(e);
+                }
+            }
+        }
+        errors.extend(inner.unexpected_calls.iter().cloned());
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(VerificationReport { errors })
+        }
+    }
+
+    // Remove all Expectations, Eras and unexpected calls, as if newly created.
+    pub fn clear(&self) {
+        // Lock our inner mutex
+        let mut inner = self.lock();
+
+        inner.current_unverified_era = 0;
+        inner.eras = vec![Era::new()];
+        inner.all_eras_complete = false;
+        inner.expectations = HandleBox::new();
+        inner.unexpected_calls.clear();
+        if let Some(ref mut log) = inner.call_log {
+            log.clear();
+        }
+    }
+
     /// (For testing) Get the number of total Expectations in the store.
     #[allow(dead_code)]
     fn exp_count(&self) -> usize {
-        self.0.lock().unwrap().expectations.map.len()
+        self.lock().expectations.map.len()
     }
 
     /// (For testing) Get the number of total Eras in the store.
     #[allow(dead_code)]
     fn era_count(&self) -> usize {
-        self.0.lock().unwrap().eras.len()
+        self.lock().eras.len()
     }
 }
 
@@ -186,24 +478,30 @@ impl<'a, I, O> ExpectationEditor<'a, I, O> where
     pub(crate) fn constrain<C>(&self, constraint: C) where
         C: Constraint<I> + 'static
     {
-        self.store.0.lock().unwrap().expectations.get_mut(&self.id).unwrap().as_any().downcast_mut::<Expectation<I, O>>().unwrap().constrain(constraint);
+        self.store.lock().expectations.get_mut(&self.id).unwrap().as_any().downcast_mut::<Expectation<I, O>>().unwrap().constrain(constraint);
     }
 
     pub(crate) fn set_modification<F>(&mut self, modification_behavior: F) where
-        F: 'static + FnMut(&mut I)
+        F: 'static + FnMut(&mut I) + MaybeSend
     {
-        self.store.0.lock().unwrap().expectations.get_mut(&self.id).unwrap().as_any().downcast_mut::<Expectation<I, O>>().unwrap().set_modification(modification_behavior);
+        self.store.lock().expectations.get_mut(&self.id).unwrap().as_any().downcast_mut::<Expectation<I, O>>().unwrap().set_modification(modification_behavior);
     }
 
     pub(crate) fn set_return<F>(&mut self, return_behavior: F) where
-        F: 'static + FnMut(I) -> O
+        F: 'static + FnMut(I) -> O + MaybeSend
     {
-        self.store.0.lock().unwrap().expectations.get_mut(&self.id).unwrap().as_any().downcast_mut::<Expectation<I, O>>().unwrap().set_return(return_behavior);
+        self.store.lock().expectations.get_mut(&self.id).unwrap().as_any().downcast_mut::<Expectation<I, O>>().unwrap().set_return(return_behavior);
+    }
+
+    pub(crate) fn set_future_return<F>(&mut self, future_behavior: F) where
+        F: 'static + FnMut(I) -> MockFuture<O> + MaybeSend
+    {
+        self.store.lock().expectations.get_mut(&self.id).unwrap().as_any().downcast_mut::<Expectation<I, O>>().unwrap().set_future_return(future_behavior);
     }
 
     #[allow(dead_code)]
     fn verify(&self) -> ExpectationResult {
-        self.store.0.lock().unwrap().expectations.get_mut(&self.id).unwrap().verify()
+        self.store.lock().expectations.get_mut(&self.id).unwrap().verify()
     }
 }
 
@@ -215,6 +513,11 @@ impl<'a, I, O> ExpectationEditor<'a, I, O> where
 pub(crate) struct ExpectationMatcher<'a, I, O> {
     ids: Vec<ExpectationId>,
     sig: MethodSig<I, O>,
+    // Set when a generic method is called with types that it has no
+    // Expectations for.
+    expected_types: Option<String>,
+    // Prints the parameters of calls, if the method's key knows how.
+    format: Option<fn(&I) -> String>,
     store: &'a ExpectationStore
 }
 
@@ -222,32 +525,134 @@ impl<'a, I, O> ExpectationMatcher<'a, I, O> where
     I: 'static,
     O: 'static
 {
-    /// Tell each matched Expectation that this method was called.
-    #[allow(unused_must_use)]
+    /// Print parameters with the given function in the call log and in
+    /// errors, instead of as `<unknown>`.
+    pub fn with_format(mut self, format: Option<fn(&I) -> String>) -> Self {
+        self.format = format;
+        self
+    }
+
+    /// Tell the matched Expectation that this method was called.
     pub fn was_called(self, params: I) -> Self {
+        self.store.log_call(&self.sig.name, &params, self.format);
         let cell = RefCell::new(params);
-        for id in self.ids.iter() {
-            self.store.0.lock().unwrap().expectations.get_mut(&id).unwrap().as_any().downcast_mut::<Expectation<I, O>>().unwrap().handle_call(&cell);
+        if let Some(id) = self.select(&cell) {
+            self.store.lock().expectations.get_mut(&id).unwrap().as_any().downcast_mut::<Expectation<I, O>>().unwrap().handle_call(&cell);
         }
         self
     }
 
+    /// Same as `was_called()`, but if the user provided a closure with
+    /// `TrackedMethod.returning()` or a delegate, it is run for its side
+    /// effects and the result is discarded.
+    pub fn was_called_responding(self, params: I) -> Self {
+        self.store.log_call(&self.sig.name, &params, self.format);
+        let cell = RefCell::new(params);
+        let selected = self.select(&cell);
+        self.respond(selected, cell);
+        self
+    }
+
     /// Same as `was_called()`, but returns a value as well.
     ///
     /// Returns the result of the closure the user provided with `TrackedMethod.returning()`.
     ///
-    /// If multiple Expectations are matched, the last one matched is used.
-    ///
     /// If no closure was specified or no expectations matched, this method panics.
-    #[allow(unused_must_use)]
-    pub fn was_called_returning(mut self, params: I) -> O {
+    pub fn was_called_returning(self, params: I) -> O {
+        self.store.log_call(&self.sig.name, &params, self.format);
         let cell = RefCell::new(params);
-        if let Some(id) = self.ids.pop() {
-            self.store.0.lock().unwrap().expectations.get_mut(&id).unwrap().as_any().downcast_mut::<Expectation<I, O>>().unwrap().handle_call(&cell);
-            let result = self.store.0.lock().unwrap().expectations.get_mut(&id).unwrap().as_any().downcast_mut::<Expectation<I, O>>().unwrap().return_value_for(cell);
-            result
-        } else {
-            panic!("Can't return a value for method `{}` with no matching expectations.", self.sig.name);
+        let selected = self.select(&cell);
+        match self.respond(selected, cell) {
+            Some(result) => result,
+            None if selected.is_some() => {
+                panic!("No return closure specified for `{}`, which should return.", self.sig.name);
+            },
+            None => self.no_matches()
+        }
+    }
+
+    /// Same as `was_called_returning()`, but for `async` methods. The result is
+    /// wrapped in a future, unless the matched Expectation produces futures itself.
+    ///
+    /// Like `was_called()`, methods that return `()` don't need a return closure.
+    pub fn was_called_async(self, params: I) -> MockFuture<O> {
+        self.store.log_call(&self.sig.name, &params, self.format);
+        let cell = RefCell::new(params);
+        let selected = self.select(&cell);
+        if let Some(id) = selected {
+            let mut inner = self.store.lock();
+            let expectation = inner.expectations.get_mut(&id).unwrap().as_any().downcast_mut::<Expectation<I, O>>().unwrap();
+            if expectation.has_future_behavior() {
+                expectation.handle_call(&cell);
+                return expectation.future_for(cell);
+            }
+        }
+        match self.respond(selected, cell).or_else(unit_value::<O>) {
+            Some(result) => MockFuture::ready(result),
+            None if selected.is_some() => {
+                panic!("No return closure specified for `{}`, which should return.", self.sig.name);
+            },
+            None => self.no_matches()
+        }
+    }
+
+    fn no_matches(&self) -> ! {
+        match self.expected_types {
+            Some(ref expected_types) => {
+                panic!("Can't return a value for method `{}` called with `{}`, which only has expectations for {}.", self.sig.name, TypeSig::of::<I, O>(), expected_types);
+            },
+            None => {
+                panic!("Can't return a value for method `{}` with no matching expectations.", self.sig.name);
+            }
+        }
+    }
+
+    // Let the selected Expectation handle the call, then produce a result from
+    // its return closure if it has one, or from the method's delegate otherwise.
+    fn respond(&self, selected: Option<ExpectationId>, cell: RefCell<I>) -> Option<O> {
+        let mut inner = self.store.lock();
+        if let Some(id) = selected {
+            let expectation = inner.expectations.get_mut(&id).unwrap().as_any().downcast_mut::<Expectation<I, O>>().unwrap();
+            expectation.handle_call(&cell);
+            if expectation.has_return_behavior() {
+                return Some(expectation.return_value_for(cell));
+            }
+        }
+        match inner.delegates.get_mut(&self.sig.name) {
+            Some(delegate) => {
+                let delegate = delegate.as_any().downcast_mut::<Delegate<I, O>>().unwrap();
+                Some(delegate.call(cell.into_inner()))
+            },
+            None => None
+        }
+    }
+
+    // Pick the Expectation that a call with these parameters belongs to.
+    //
+    // If only one Expectation was matched by name, it is always used so that
+    // its constraints can report what went wrong. Otherwise the first one that
+    // accepts the parameters is used, and if none do, the call is noted as
+    // unexpected. Calls that match nothing by name are only noted in strict mode.
+    fn select(&self, cell: &RefCell<I>) -> Option<ExpectationId> {
+        match self.ids.len() {
+            0 => {
+                if let Some(ref expected_types) = self.expected_types {
+                    self.store.add_unexpected_types(&self.sig.name, expected_types, TypeSig::of::<I, O>());
+                } else if self.store.is_strict() {
+                    self.store.add_unexpected_call(&self.sig.name, &*cell.borrow(), self.format);
+                }
+                None
+            },
+            1 => Some(self.ids[0]),
+            _ => {
+                let selected = self.ids.iter().cloned().find(|id| {
+                    self.store.lock().expectations.get_mut(id).unwrap().as_any().downcast_mut::<Expectation<I, O>>().unwrap().accepts(cell)
+                });
+                if selected.is_none() {
+                    self.store.add_unexpected_call(&self.sig.name, &*cell.borrow(), self.format);
+                }
+                selected
+            }
         }
     }
 
@@ -258,11 +663,30 @@ impl<'a, I, O> ExpectationMatcher<'a, I, O> where
     }
 }
 
+// Print a call's parameters with the given function, or as `<unknown>`.
+fn render_params<I>(params: &I, format: Option<fn(&I) -> String>) -> String {
+    match format {
+        Some(format) => format(params),
+        None => format!("{:?}", DebugIt(params))
+    }
+}
+
+// Returns `Some(())` if `O` is `()`, so that results can be produced for
+// methods that don't return anything.
+fn unit_value<O>() -> Option<O> where
+    O: 'static
+{
+    let unit: Box<dyn Any> = Box::new(());
+    unit.downcast::<O>().ok().map(|unit| *unit)
+}
+
 #[cfg(test)]
 mod store_tests {
     use super::*;
     use constraint::ConstraintError;
     use constraint::stock::always::{AlwaysFail, AlwaysPass};
+    use constraint::stock::params::Params;
+    use method::format_debug;
 
     #[test]
     fn test_new() {
@@ -329,6 +753,69 @@ mod store_tests {
         assert_eq!(r.constraint_err, ConstraintError::AlwaysFail, "Store error should contain the correct Constraint error");
     }
 
+    #[test]
+    fn test_verify_all() {
+        let s = ExpectationStore::new();
+        let mut e: Expectation<(), ()> = Expectation::new("zooks");
+        e.constrain(AlwaysFail);
+        s.add(e);
+        s.new_era();
+        let mut e: Expectation<(), ()> = Expectation::new("zonk");
+        e.constrain(AlwaysPass);
+        s.add(e);
+        let mut e: Expectation<(), ()> = Expectation::new("zinger");
+        e.constrain(AlwaysFail);
+        s.add(e);
+
+        let r = s.verify_all();
+
+        assert!(r.is_err(), "Store should fail");
+        let names: Vec<MethodName> = r.unwrap_err().errors.into_iter().map(|e| e.method_name).collect();
+        assert_eq!(names, vec!["zooks", "zinger"], "Report should contain every failing Expectation in every Era");
+    }
+
+    #[test]
+    fn test_clear() {
+        let s = ExpectationStore::new();
+        let mut e: Expectation<(), ()> = Expectation::new("zooks");
+        e.constrain(AlwaysFail);
+        s.add(e);
+        s.new_era();
+
+        s.clear();
+
+        assert!(s.verify_all().is_ok(), "Store should be Ok after clearing");
+        assert_eq!(s.era_count(), 1, "Store should have one Era after clearing");
+        assert_eq!(s.exp_count(), 0, "Store should have no Expectations after clearing");
+    }
+
+    #[test]
+    fn test_log_call() {
+        let s = ExpectationStore::new();
+        s.record_calls();
+
+        s.matcher_for::<(), ()>("frob").was_called(());
+        s.new_era();
+        s.matcher_for::<i32, ()>("star").with_format(Some(format_debug::<i32>)).was_called(5);
+
+        let log = s.call_log();
+        assert_eq!(log.len(), 2, "Log should contain every call");
+        assert_eq!(log[0].args, "<unknown>", "Record should show params without a format as unknown");
+        assert_eq!(log[1].method_name, "star", "Record should have the correct method name");
+        assert_eq!(log[1].era, 1, "Record should have the correct Era");
+        assert_eq!(log[1].sequence, 1, "Record should have the correct sequence number");
+        assert_eq!(log[1].args, "5", "Record should have the params printed");
+    }
+
+    #[test]
+    fn test_log_call_not_recording() {
+        let s = ExpectationStore::new();
+
+        s.matcher_for::<(), ()>("frob").was_called(());
+
+        assert!(s.call_log().is_empty(), "Log should be empty when not recording");
+    }
+
     #[test]
     fn test_match() {
         let s = ExpectationStore::new();
@@ -353,8 +840,8 @@ mod store_tests {
 
         s.new_era();
 
-        // Add the same method twice! It doesn't make sense in practice, but we want
-        // to only return one Id, signifying that the first Era was matched against.
+        // Add the same method twice, but we want to only return one Id,
+        // signifying that the first Era was matched against.
         let mut e: Expectation<(), ()> = Expectation::new("frob");
         e.constrain(AlwaysFail);
         s.add(e);
@@ -367,6 +854,63 @@ mod store_tests {
         assert_eq!(m.id_count(), 1, "Ids matched should be 1");
     }
 
+    #[test]
+    fn test_match_multiple() {
+        let s = ExpectationStore::new();
+        let e: Expectation<(), ()> = Expectation::new("frob");
+        s.add(e);
+        let e: Expectation<(), ()> = Expectation::new("frob");
+        s.add(e);
+
+        let m = s.matcher_for::<(), ()>("frob");
+
+        assert_eq!(m.id_count(), 2, "Ids matched should be 2");
+    }
+
+    #[test]
+    fn test_unexpected_call() {
+        let s = ExpectationStore::new();
+        let mut e: Expectation<i32, ()> = Expectation::new("frob");
+        e.constrain(Params::new(1));
+        s.add(e);
+        let mut e: Expectation<i32, ()> = Expectation::new("frob");
+        e.constrain(Params::new(2));
+        s.add(e);
+
+        s.matcher_for::<i32, ()>("frob").with_format(Some(format_debug::<i32>)).was_called(3);
+        let r = s.verify();
+
+        assert!(r.is_err(), "Store should fail");
+        let r = r.unwrap_err();
+        assert_eq!(r.method_name, "frob", "Store error should have the correct method name");
+        assert_eq!(r.constraint_err, ConstraintError::CallNotExpected("3".to_owned()), "Store error should contain the correct Constraint error");
+    }
+
+    #[test]
+    fn test_strict_unexpected_call() {
+        let s = ExpectationStore::new();
+        s.set_strict(true);
+
+        s.matcher_for::<(), ()>("frob").with_format(Some(format_debug::<()>)).was_called(());
+        let r = s.verify();
+
+        assert!(r.is_err(), "Store should fail");
+        let r = r.unwrap_err();
+        assert_eq!(r.method_name, "frob", "Store error should have the correct method name");
+        assert_eq!(r.constraint_err, ConstraintError::CallNotExpected("()".to_owned()), "Store error should contain the correct Constraint error");
+    }
+
+    #[test]
+    fn test_unexpected_call_without_format() {
+        let s = ExpectationStore::new();
+        s.set_strict(true);
+
+        s.matcher_for::<i32, ()>("frob").was_called(3);
+        let r = s.verify();
+
+        assert_eq!(r.unwrap_err().constraint_err, ConstraintError::CallNotExpected("<unknown>".to_owned()), "Store error should contain the correct Constraint error");
+    }
+
     #[test]
     fn test_match_current_era_passed() {
         let s = ExpectationStore::new();
@@ -375,7 +919,7 @@ mod store_tests {
         s.add(e);
 
         // Cheat and bump up the current unverified era to the end
-        s.0.lock().unwrap().current_unverified_era = 1; 
+        s.lock().current_unverified_era = 1; 
 
         let m = s.matcher_for::<(), ()>("buzz");
 
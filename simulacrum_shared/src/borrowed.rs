@@ -0,0 +1,227 @@
+//! Stand-ins for reference parameters, so that methods taking references with
+//! any lifetime can be mocked.
+//!
+//! Expectations can only store `'static` types, so a `&T` parameter is passed
+//! to them as a `Borrowed<T>` and a `&mut T` parameter as a `BorrowedMut<T>`.
+//! These are created from a `Scope`, and their values are only reachable
+//! through closures passed to `with()` and `with_mut()`, so references to them
+//! can't outlive the call. Using one after the call that created it has
+//! returned is a panic rather than undefined behavior.
+
+use std::cell::Cell;
+use std::fmt;
+use std::marker::PhantomData;
+use std::rc::Rc;
+
+/// The duration of a single call to a mock object. References borrowed from a
+/// `Scope` are valid until `Scope::with()` returns.
+pub struct Scope<'a> {
+    alive: Rc<Cell<bool>>,
+    // Invariant, so that references with shorter lifetimes can't be borrowed.
+    _borrows: PhantomData<fn(&'a ()) -> &'a ()>
+}
+
+impl<'a> Scope<'a> {
+    /// Run `f` with a new `Scope`. Anything borrowed from it can no longer be
+    /// used once this returns, even if `f` panics.
+    pub fn with<R, F>(f: F) -> R where
+        F: FnOnce(&Scope<'a>) -> R
+    {
+        let scope = Scope {
+            alive: Rc::new(Cell::new(true)),
+            _borrows: PhantomData
+        };
+        f(&scope)
+    }
+
+    /// Wrap a shared reference so that it can be passed to an Expectation.
+    pub fn borrow<T>(&self, value: &'a T) -> Borrowed<T> where
+        T: ?Sized + 'static
+    {
+        Borrowed {
+            value: value as *const T,
+            alive: self.alive.clone()
+        }
+    }
+
+    /// Wrap a mutable reference so that it can be passed to an Expectation.
+    pub fn borrow_mut<T>(&self, value: &'a mut T) -> BorrowedMut<T> where
+        T: ?Sized + 'static
+    {
+        BorrowedMut {
+            value: value as *mut T,
+            alive: self.alive.clone()
+        }
+    }
+}
+
+impl<'a> Drop for Scope<'a> {
+    fn drop(&mut self) {
+        self.alive.set(false);
+    }
+}
+
+fn check_alive(alive: &Cell<bool>) {
+    if !alive.get() {
+        panic!("A borrowed parameter was used after the call that it was passed to returned.");
+    }
+}
+
+/// A shared reference parameter, borrowed for the duration of a call.
+pub struct Borrowed<T: ?Sized> {
+    value: *const T,
+    alive: Rc<Cell<bool>>
+}
+
+impl<T: ?Sized> Borrowed<T> {
+    /// Returns `true` if the call that this was passed to is still in progress.
+    pub fn is_alive(&self) -> bool {
+        self.alive.get()
+    }
+
+    /// Run `f` with the borrowed value. Panics if the call that this was
+    /// passed to has returned.
+    ///
+    /// The reference can't be kept beyond `f`:
+    ///
+    /// ```compile_fail
+    /// # use simulacrum_shared::Scope;
+    /// let value = 5;
+    /// let escaped = Scope::with(|scope| scope.borrow(&value).with(|value| value));
+    /// ```
+    pub fn with<R, F>(&self, f: F) -> R where
+        F: FnOnce(&T) -> R
+    {
+        check_alive(&self.alive);
+        f(unsafe { &*self.value })
+    }
+}
+
+impl<T: ?Sized> Clone for Borrowed<T> {
+    fn clone(&self) -> Self {
+        Borrowed {
+            value: self.value,
+            alive: self.alive.clone()
+        }
+    }
+}
+
+impl<T: ?Sized + fmt::Debug> fmt::Debug for Borrowed<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.is_alive() {
+            self.with(|value| write!(f, "&{:?}", value))
+        } else {
+            write!(f, "&<expired>")
+        }
+    }
+}
+
+/// A mutable reference parameter, borrowed for the duration of a call.
+pub struct BorrowedMut<T: ?Sized> {
+    value: *mut T,
+    alive: Rc<Cell<bool>>
+}
+
+impl<T: ?Sized> BorrowedMut<T> {
+    /// Returns `true` if the call that this was passed to is still in progress.
+    pub fn is_alive(&self) -> bool {
+        self.alive.get()
+    }
+
+    /// Run `f` with the borrowed value. Panics if the call that this was
+    /// passed to has returned.
+    pub fn with<R, F>(&self, f: F) -> R where
+        F: FnOnce(&T) -> R
+    {
+        check_alive(&self.alive);
+        f(unsafe { &*self.value })
+    }
+
+    /// Run `f` with the borrowed value, which it can modify. Panics if the call
+    /// that this was passed to has returned.
+    pub fn with_mut<R, F>(&mut self, f: F) -> R where
+        F: FnOnce(&mut T) -> R
+    {
+        check_alive(&self.alive);
+        f(unsafe { &mut *self.value })
+    }
+}
+
+impl<T: ?Sized + fmt::Debug> fmt::Debug for BorrowedMut<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.is_alive() {
+            self.with(|value| write!(f, "&mut {:?}", value))
+        } else {
+            write!(f, "&mut <expired>")
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_borrow() {
+        let value = 5;
+        let sum = Scope::with(|scope| {
+            let borrowed = scope.borrow(&value);
+            borrowed.with(|value| value + 1)
+        });
+        assert_eq!(sum, 6);
+    }
+
+    #[test]
+    fn test_borrow_unsized() {
+        let text = String::from("hello");
+        let numbers = [1, 2, 3];
+        Scope::with(|scope| {
+            assert_eq!(scope.borrow(text.as_str()).with(|text| text.to_owned()), "hello");
+            assert_eq!(scope.borrow(&numbers[..]).with(|numbers| numbers.len()), 3);
+        });
+    }
+
+    #[test]
+    fn test_borrow_mut() {
+        let mut flag = true;
+        Scope::with(|scope| {
+            let mut borrowed = scope.borrow_mut(&mut flag);
+            borrowed.with_mut(|flag| *flag = false);
+        });
+        assert!(!flag);
+    }
+
+    #[test]
+    #[should_panic(expected = "used after the call")]
+    fn test_expired() {
+        let value = 5;
+        let borrowed = Scope::with(|scope| scope.borrow(&value));
+        assert!(!borrowed.is_alive());
+
+        // Panic: The scope has ended
+        borrowed.with(|_| ());
+    }
+
+    #[test]
+    #[should_panic(expected = "used after the call")]
+    fn test_leaked() {
+        let value = String::from("hello");
+        let leaked: &'static Borrowed<String> = Scope::with(|scope| {
+            Box::leak(Box::new(scope.borrow(&value)))
+        });
+        drop(value);
+
+        // Panic: A leaked Borrowed still can't reach the value
+        leaked.with(|value| value.len());
+    }
+
+    #[test]
+    fn test_debug() {
+        let value = 5;
+        let borrowed = Scope::with(|scope| {
+            assert_eq!(format!("{:?}", scope.borrow(&value)), "&5");
+            scope.borrow(&value)
+        });
+        assert_eq!(format!("{:?}", borrowed), "&<expired>");
+    }
+}
@@ -2,6 +2,10 @@
 
 extern crate debugit;
 
+pub mod borrowed;
+pub mod send;
 pub mod validator;
 
+pub use borrowed::{Borrowed, BorrowedMut, Scope};
+pub use send::MaybeSend;
 pub use validator::Validator;
\ No newline at end of file
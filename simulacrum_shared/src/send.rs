@@ -0,0 +1,21 @@
+//! Bounds that change depending on whether the `sync` feature is enabled.
+
+/// With the `sync` feature enabled, this is the same as `Send`. Otherwise it is
+/// implemented for every type.
+///
+/// Use it as a bound on anything that will be stored inside of a mock object.
+#[cfg(feature = "sync")]
+pub trait MaybeSend: Send {}
+
+#[cfg(feature = "sync")]
+impl<T: Send + ?Sized> MaybeSend for T {}
+
+/// With the `sync` feature enabled, this is the same as `Send`. Otherwise it is
+/// implemented for every type.
+///
+/// Use it as a bound on anything that will be stored inside of a mock object.
+#[cfg(not(feature = "sync"))]
+pub trait MaybeSend {}
+
+#[cfg(not(feature = "sync"))]
+impl<T: ?Sized> MaybeSend for T {}
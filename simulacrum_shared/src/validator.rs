@@ -1,10 +1,12 @@
 use debugit::DebugIt;
 
+use send::MaybeSend;
+
 /// A `Validator` is an object that knows how to validate method parameters.
 ///
 /// To use these, you typically pass them to the `.with()` method for use with
 /// the `Params` Constraint.
-pub trait Validator<I> {
+pub trait Validator<I: ?Sized>: MaybeSend {
     /// This object has been called with the given parameters. Return `true`
     /// if they are acceptable, and `false` if they are not.
     fn validate(&mut self, param: &I) -> bool;
@@ -14,7 +16,7 @@ pub trait Validator<I> {
 }
 
 /// `Validator` is automatically implemented for types that implement `PartialEq`.
-impl<I: PartialEq> Validator<I> for I {
+impl<I: PartialEq + MaybeSend> Validator<I> for I {
     fn validate(&mut self, param: &I) -> bool {
         &*param == self
     }
@@ -24,6 +26,53 @@ impl<I: PartialEq> Validator<I> for I {
      }
 }
 
+/// String slices can be validated against string literals and `String`s.
+impl Validator<str> for &'static str {
+    fn validate(&mut self, param: &str) -> bool {
+        param == *self
+    }
+
+    fn print(&self) -> String {
+        format!("{:?}", self)
+    }
+}
+
+impl Validator<str> for String {
+    fn validate(&mut self, param: &str) -> bool {
+        param == self.as_str()
+    }
+
+    fn print(&self) -> String {
+        format!("{:?}", self)
+    }
+}
+
+/// Slices can be validated against `Vec`s and other slices.
+impl<T> Validator<[T]> for Vec<T> where
+    T: PartialEq + MaybeSend
+{
+    fn validate(&mut self, param: &[T]) -> bool {
+        param == &self[..]
+    }
+
+    fn print(&self) -> String {
+        format!("{:?}", DebugIt(self))
+    }
+}
+
+impl<T> Validator<[T]> for &'static [T] where
+    T: PartialEq + 'static,
+    &'static [T]: MaybeSend
+{
+    fn validate(&mut self, param: &[T]) -> bool {
+        param == *self
+    }
+
+    fn print(&self) -> String {
+        format!("{:?}", DebugIt(self))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -34,4 +83,18 @@ mod tests {
         assert!(555.validate(&v));
         assert!(!666.validate(&v));
     }
+
+    #[test]
+    fn test_str_validate() {
+        let v = String::from("hello");
+        assert!("hello".validate(v.as_str()));
+        assert!(!String::from("bye").validate(v.as_str()));
+    }
+
+    #[test]
+    fn test_slice_validate() {
+        let v = [1, 2, 3];
+        assert!(vec![1, 2, 3].validate(&v[..]));
+        assert!(!vec![1, 2].validate(&v[..]));
+    }
 }
\ No newline at end of file
@@ -1,18 +1,31 @@
-use simulacrum_shared::Validator;
+use simulacrum_shared::{MaybeSend, Validator};
 
 /// A closure that will be called with the parameters to validate that they 
 /// conform to the requirements.
-pub struct Check<I>(Box<FnMut(&I) -> bool>);
+pub struct Check<I>(Box<dyn CheckFn<I>>);
+
+// Lets the closure be `Send` only when `MaybeSend` requires it.
+trait CheckFn<I>: MaybeSend {
+    fn check(&mut self, param: &I) -> bool;
+}
+
+impl<I, F> CheckFn<I> for F where
+    F: FnMut(&I) -> bool + MaybeSend
+{
+    fn check(&mut self, param: &I) -> bool {
+        self(param)
+    }
+}
 
 pub fn passes<I, F>(closure: F) -> Check<I> where
-    F: FnMut(&I) -> bool + 'static
+    F: FnMut(&I) -> bool + MaybeSend + 'static
 {
     Check(Box::new(closure))
 }
 
 impl<I> Validator<I> for Check<I> {
     fn validate(&mut self, param: &I) -> bool {
-        (self.0)(param)
+        self.0.check(param)
     }
 
      fn print(&self) -> String {
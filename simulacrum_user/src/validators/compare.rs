@@ -1,5 +1,5 @@
 use debugit::DebugIt;
-use simulacrum_shared::Validator;
+use simulacrum_shared::{MaybeSend, Validator};
 
 pub struct GreaterThan<I: PartialOrd>(I);
 
@@ -8,7 +8,7 @@ pub fn gt<I: PartialOrd>(other: I) -> GreaterThan<I> {
     GreaterThan(other)
 }
 
-impl<I: PartialOrd> Validator<I> for GreaterThan<I> {
+impl<I: PartialOrd + MaybeSend> Validator<I> for GreaterThan<I> {
     fn validate(&mut self, param: &I) -> bool {
         *param > self.0
     }
@@ -25,7 +25,7 @@ pub fn lt<I: PartialOrd>(other: I) -> LessThan<I> {
     LessThan(other)
 }
 
-impl<I: PartialOrd> Validator<I> for LessThan<I> {
+impl<I: PartialOrd + MaybeSend> Validator<I> for LessThan<I> {
     fn validate(&mut self, param: &I) -> bool {
         *param < self.0
     }
@@ -1,17 +1,45 @@
-use simulacrum_shared::Validator;
+use simulacrum_shared::{Borrowed, BorrowedMut, MaybeSend, Validator};
 
 use std::marker::PhantomData;
 
-/// Parameter(s) must equal the provided value.
-pub struct Deref<I, V>(V, PhantomData<I>) where V: Validator<I>;
+/// The value behind a reference parameter must pass the provided `Validator`.
+pub struct Deref<I: ?Sized, V>(V, PhantomData<fn(&I)>) where V: Validator<I>;
 
 pub fn deref<I, V>(validator: V) -> Deref<I, V> where
+    I: ?Sized,
     V: Validator<I>
 {
     Deref(validator, PhantomData)
 }
 
+impl<I, V> Validator<Borrowed<I>> for Deref<I, V> where
+    I: ?Sized,
+    V: Validator<I>
+{
+    fn validate(&mut self, param: &Borrowed<I>) -> bool {
+        param.with(|value| self.0.validate(value))
+    }
+
+    fn print(&self) -> String {
+        format!("& {}", self.0.print())
+    }
+}
+
+impl<I, V> Validator<BorrowedMut<I>> for Deref<I, V> where
+    I: ?Sized,
+    V: Validator<I>
+{
+    fn validate(&mut self, param: &BorrowedMut<I>) -> bool {
+        param.with(|value| self.0.validate(value))
+    }
+
+    fn print(&self) -> String {
+        format!("&mut {}", self.0.print())
+    }
+}
+
 impl<I, V> Validator<*mut I> for Deref<I, V> where
+    I: MaybeSend,
     V: Validator<I>
 {
     fn validate(&mut self, param: &*mut I) -> bool {
@@ -26,6 +54,7 @@ impl<I, V> Validator<*mut I> for Deref<I, V> where
 }
 
 impl<I, V> Validator<*const I> for Deref<I, V> where
+    I: MaybeSend,
     V: Validator<I>
 {
     fn validate(&mut self, param: &*const I) -> bool {
@@ -42,6 +71,28 @@ impl<I, V> Validator<*const I> for Deref<I, V> where
 #[cfg(test)]
 mod tests {
     use super::*;
+    use simulacrum_shared::Scope;
+
+    #[test]
+    fn test_validate_borrowed() {
+        let v = 888;
+        let s = String::from("hello");
+        Scope::with(|scope| {
+            assert!(deref(888).validate(&scope.borrow(&v)));
+            assert!(!deref(555).validate(&scope.borrow(&v)));
+            assert!(deref("hello").validate(&scope.borrow(s.as_str())));
+        });
+    }
+
+    #[test]
+    fn test_validate_borrowed_mut() {
+        let mut v = 888;
+        Scope::with(|scope| {
+            let b = scope.borrow_mut(&mut v);
+            assert!(deref(888).validate(&b));
+            assert!(!deref(555).validate(&b));
+        });
+    }
 
     #[test]
     fn test_validate_const() {